@@ -0,0 +1,47 @@
+//! A compact binary encoding of game states, for hosts that play many
+//! games per second and spend most of that time in JSON.
+//!
+//! With the `binary-state` feature a state can also be written as
+//! MessagePack, with structs as arrays rather than maps, so no field names
+//! are stored. Fields left out with `skip_serializing_if` must come last and
+//! have a `#[serde(default)]`, which is how the bundled games already write
+//! their optional fields. Without the feature only JSON is available and
+//! the conversions fail with `unsupported_format`.
+
+use crate::{Game, GameError};
+
+/// The state formats this build reads and writes, as listed by
+/// `get_state_formats`. JSON is always first.
+pub fn formats() -> &'static [&'static str] {
+    if cfg!(feature = "binary-state") {
+        &["json", "msgpack"]
+    } else {
+        &["json"]
+    }
+}
+
+#[cfg(feature = "binary-state")]
+pub fn encode<G: Game>(state: &G::State) -> Result<Vec<u8>, GameError> {
+    rmp_serde::to_vec(state).map_err(|error| GameError::new("invalid_state", format!("state cannot be encoded: {}", error)))
+}
+
+#[cfg(feature = "binary-state")]
+pub fn decode<G: Game>(bytes: &[u8]) -> Result<G::State, GameError> {
+    rmp_serde::from_slice(bytes)
+        .map_err(|error| GameError::new("invalid_state", format!("state is not valid MessagePack for this game: {}", error)))
+}
+
+#[cfg(not(feature = "binary-state"))]
+fn unsupported() -> GameError {
+    GameError::new("unsupported_format", "this module was built without binary states")
+}
+
+#[cfg(not(feature = "binary-state"))]
+pub fn encode<G: Game>(_state: &G::State) -> Result<Vec<u8>, GameError> {
+    Err(unsupported())
+}
+
+#[cfg(not(feature = "binary-state"))]
+pub fn decode<G: Game>(_bytes: &[u8]) -> Result<G::State, GameError> {
+    Err(unsupported())
+}
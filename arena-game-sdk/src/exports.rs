@@ -0,0 +1,595 @@
+//! Bodies of the exports generated by `export_game!`, kept generic so the
+//! macro itself only forwards to them. A state that cannot be read turns
+//! string results into an `invalid_state` error and `is_game_over` into -1.
+
+use std::os::raw::c_char;
+
+use crate::binary;
+use crate::ffi::{from_c_string, read_binary_state, read_state, to_buffer, to_c_result, to_c_string, to_json, write_state};
+use crate::guard::{set_last_error, take_last_error};
+use crate::log::Level;
+use crate::registry;
+use crate::{host_log, Game, GameError};
+
+pub fn get_initial_state<G: Game>() -> *mut c_char {
+    write_state::<G>(&G::initial_state())
+}
+
+pub fn get_valid_moves<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).and_then(|state| {
+        let moves = G::legal_moves(&state);
+        host_log!(Level::Debug, "{} legal moves for {}", moves.len(), G::current_player(&state));
+        to_json(&moves)
+    }))
+}
+
+pub fn apply_move<G: Game>(state_ptr: *const c_char, move_ptr: *const c_char) -> *mut c_char {
+    let mut state = match read_state::<G>(state_ptr) {
+        Ok(state) => state,
+        Err(error) => return to_c_string(error.to_result()),
+    };
+    let move_str = from_c_string(move_ptr);
+
+    match G::apply(&mut state, &move_str) {
+        Ok(()) => write_state::<G>(&state),
+        Err(error) => {
+            host_log!(Level::Info, "rejected move {:?}: {} ({})", move_str, error.message, error.code);
+            to_c_string(error.to_result())
+        }
+    }
+}
+
+/// Plays `moves_ptr`, a JSON array of moves, in order. A rejected move
+/// fails the whole call with the move's error code and its index in the
+/// message; the state passed in is never changed.
+pub fn apply_moves<G: Game>(state_ptr: *const c_char, moves_ptr: *const c_char) -> *mut c_char {
+    let mut state = match read_state::<G>(state_ptr) {
+        Ok(state) => state,
+        Err(error) => return to_c_string(error.to_result()),
+    };
+    let moves: Vec<String> = match serde_json::from_str(&from_c_string(moves_ptr)) {
+        Ok(moves) => moves,
+        Err(error) => {
+            let error = GameError::new("invalid_moves", format!("moves must be a JSON array of strings: {}", error));
+            return to_c_string(error.to_result());
+        }
+    };
+
+    for (index, move_str) in moves.iter().enumerate() {
+        if let Err(error) = G::apply(&mut state, move_str) {
+            host_log!(Level::Info, "rejected move {} {:?}: {} ({})", index, move_str, error.message, error.code);
+            let error = GameError::new(&error.code, format!("move {} ({:?}): {}", index, move_str, error.message));
+            return to_c_string(error.to_result());
+        }
+    }
+    write_state::<G>(&state)
+}
+
+/// The answers to every per-turn query in one JSON object, so the host
+/// does not send the same state to five exports. The player to move gets
+/// this to decide on a move, so a game with hidden information renders the
+/// board from their seat rather than showing them everything.
+fn turn_info<G: Game>(state: &G::State) -> String {
+    let outcome = G::outcome(state);
+    let current_player = G::current_player(state);
+    let render = if G::CAPABILITIES.hidden_info { G::render_for(state, &current_player) } else { G::render(state) };
+    serde_json::json!({
+        "valid_moves": G::legal_moves(state),
+        "game_over": outcome.is_over(),
+        "winner": outcome.winner(),
+        "current_player": current_player,
+        "render": render,
+    })
+    .to_string()
+}
+
+pub fn get_turn_info<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).map(|state| turn_info::<G>(&state)))
+}
+
+pub fn is_game_over<G: Game>(state_ptr: *const c_char) -> i32 {
+    read_state::<G>(state_ptr).map_or(-1, |state| if G::outcome(&state).is_over() { 1 } else { 0 })
+}
+
+pub fn get_winner<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).map(|state| G::outcome(&state).winner().to_string()))
+}
+
+pub fn render<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).map(|state| G::render(&state)))
+}
+
+/// The player named at `player_ptr`, which must be one of the game's
+/// seats: "player1" up to "playerN".
+fn read_player<G: Game>(player_ptr: *const c_char) -> Result<String, GameError> {
+    let player = from_c_string(player_ptr);
+    let seat = player.strip_prefix("player").and_then(|seat| seat.parse::<u32>().ok());
+    match seat {
+        Some(seat) if (1..=G::CAPABILITIES.players).contains(&seat) && player == format!("player{}", seat) => Ok(player),
+        _ => Err(GameError::new(
+            "invalid_player",
+            format!("unknown player {:?}; expected player1 to player{}", player, G::CAPABILITIES.players),
+        )),
+    }
+}
+
+pub fn get_observation<G: Game>(state_ptr: *const c_char, player_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).and_then(|state| {
+        let player = read_player::<G>(player_ptr)?;
+        Ok(G::observation(&state, &player).to_string())
+    }))
+}
+
+pub fn render_for<G: Game>(state_ptr: *const c_char, player_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).and_then(|state| {
+        let player = read_player::<G>(player_ptr)?;
+        Ok(G::render_for(&state, &player))
+    }))
+}
+
+pub fn get_game_name<G: Game>() -> *mut c_char {
+    to_c_string(G::NAME.to_string())
+}
+
+pub fn get_current_player<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).map(|state| G::current_player(&state)))
+}
+
+pub fn get_game_description<G: Game>() -> *mut c_char {
+    to_c_string(G::DESCRIPTION.to_string())
+}
+
+pub fn get_capabilities<G: Game>() -> *mut c_char {
+    to_c_string(G::CAPABILITIES.to_json())
+}
+
+/// `get_capabilities` narrowed to one state, e.g. a board too large for
+/// the game's hints.
+pub fn get_state_capabilities<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).map(|state| G::capabilities(&state).to_json()))
+}
+
+/// JSON array of the state formats `encode_state` and `decode_state`
+/// support, e.g. `["json","msgpack"]`.
+pub fn get_state_formats() -> *mut c_char {
+    to_c_result(to_json(binary::formats()))
+}
+
+/// The JSON state at `state_ptr` in binary form, as a length-prefixed
+/// buffer. Null if it cannot be converted; the error is then available
+/// from `get_last_error`.
+pub fn encode_state<G: Game>(state_ptr: *const c_char) -> *mut u8 {
+    match read_state::<G>(state_ptr).and_then(|state| binary::encode::<G>(&state)) {
+        Ok(bytes) => to_buffer(&bytes),
+        Err(error) => {
+            set_last_error(&error);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// The binary state of `len` bytes at `ptr` back as JSON.
+pub fn decode_state<G: Game>(ptr: *const u8, len: usize) -> *mut c_char {
+    match read_binary_state::<G>(ptr, len) {
+        Ok(state) => write_state::<G>(&state),
+        Err(error) => to_c_string(error.to_result()),
+    }
+}
+
+pub fn get_metadata(metadata: &[u8]) -> *mut c_char {
+    to_c_string(String::from_utf8_lossy(metadata).into_owned())
+}
+
+pub fn log_transcript<G: Game>(state_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_state::<G>(state_ptr).map(|state| G::transcript(&state)))
+}
+
+/// `{"code": ..., "message": ...}` for the last panic or unreadable state,
+/// or "" if there was none since the last call.
+pub fn get_last_error() -> *mut c_char {
+    match take_last_error() {
+        Some(error) => to_c_string(serde_json::json!({ "code": error.code, "message": error.message }).to_string()),
+        None => to_c_string(String::new()),
+    }
+}
+
+/// -1 for exports returning a number, with the reason kept for
+/// `get_last_error`.
+fn failed(error: &GameError) -> i32 {
+    set_last_error(error);
+    -1
+}
+
+pub fn handle_create<G: Game>() -> i32 {
+    registry::insert::<G>(G::initial_state())
+}
+
+pub fn handle_from_state<G: Game>(state_ptr: *const c_char) -> i32 {
+    read_state::<G>(state_ptr).map_or(-1, registry::insert::<G>)
+}
+
+pub fn handle_clone<G: Game>(handle: i32) -> i32 {
+    match registry::with_state::<G, _>(handle, G::State::clone) {
+        Ok(state) => registry::insert::<G>(state),
+        Err(error) => failed(&error),
+    }
+}
+
+pub fn handle_drop(handle: i32) {
+    registry::remove(handle);
+}
+
+/// 0 once the move is played, -1 if it was rejected or the handle is
+/// unknown; the error is then available from `get_last_error`.
+pub fn handle_apply_move<G: Game>(handle: i32, move_ptr: *const c_char) -> i32 {
+    let move_str = from_c_string(move_ptr);
+
+    match registry::with_state_mut::<G, _>(handle, |state| G::apply(state, &move_str)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(error)) => {
+            host_log!(Level::Info, "rejected move {:?}: {} ({})", move_str, error.message, error.code);
+            failed(&error)
+        }
+        Err(error) => failed(&error),
+    }
+}
+
+pub fn handle_get_state<G: Game>(handle: i32) -> *mut c_char {
+    to_c_result(registry::with_state::<G, _>(handle, to_json).and_then(|json| json))
+}
+
+pub fn handle_get_valid_moves<G: Game>(handle: i32) -> *mut c_char {
+    to_c_result(registry::with_state::<G, _>(handle, |state| to_json(&G::legal_moves(state))).and_then(|json| json))
+}
+
+pub fn handle_is_game_over<G: Game>(handle: i32) -> i32 {
+    match registry::with_state::<G, _>(handle, |state| G::outcome(state).is_over()) {
+        Ok(over) => if over { 1 } else { 0 },
+        Err(error) => failed(&error),
+    }
+}
+
+pub fn handle_get_winner<G: Game>(handle: i32) -> *mut c_char {
+    to_c_result(registry::with_state::<G, _>(handle, |state| G::outcome(state).winner().to_string()))
+}
+
+pub fn handle_get_current_player<G: Game>(handle: i32) -> *mut c_char {
+    to_c_result(registry::with_state::<G, _>(handle, G::current_player))
+}
+
+pub fn handle_render<G: Game>(handle: i32) -> *mut c_char {
+    to_c_result(registry::with_state::<G, _>(handle, G::render))
+}
+
+pub fn handle_get_turn_info<G: Game>(handle: i32) -> *mut c_char {
+    to_c_result(registry::with_state::<G, _>(handle, turn_info::<G>))
+}
+
+pub fn handle_get_observation<G: Game>(handle: i32, player_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_player::<G>(player_ptr).and_then(|player| {
+        registry::with_state::<G, _>(handle, |state| G::observation(state, &player).to_string())
+    }))
+}
+
+pub fn handle_render_for<G: Game>(handle: i32, player_ptr: *const c_char) -> *mut c_char {
+    to_c_result(read_player::<G>(player_ptr).and_then(|player| {
+        registry::with_state::<G, _>(handle, |state| G::render_for(state, &player))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::raw::c_char;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::ffi::test_util::{c, play, take};
+    use crate::{Game, GameError, Outcome};
+
+    /// Players take turns adding 1 or 2 to a running total; whoever brings
+    /// it to 5 wins.
+    struct Race;
+
+    #[derive(Serialize, Deserialize, Clone)]
+    struct RaceState {
+        total: u32,
+        moves: u32,
+    }
+
+    const GOAL: u32 = 5;
+
+    impl Game for Race {
+        type State = RaceState;
+
+        const NAME: &'static str = "Race";
+        const DESCRIPTION: &'static str = "Add 1 or 2; reach 5 to win";
+
+        fn initial_state() -> RaceState {
+            RaceState { total: 0, moves: 0 }
+        }
+
+        fn check_state(state: &RaceState) -> Result<(), String> {
+            if state.total > GOAL {
+                return Err(format!("total cannot pass {}", GOAL));
+            }
+            Ok(())
+        }
+
+        fn legal_moves(state: &RaceState) -> Vec<String> {
+            (1..=2).filter(|step| state.total + step <= GOAL && state.total < GOAL).map(|step| step.to_string()).collect()
+        }
+
+        fn apply(state: &mut RaceState, move_str: &str) -> Result<(), GameError> {
+            let step: u32 = match move_str {
+                "1" => 1,
+                "2" => 2,
+                "boom" => panic!("the game blew up"),
+                _ => return Err(GameError::new("malformed", format!("'{}' is not 1 or 2", move_str))),
+            };
+            if state.total == GOAL {
+                return Err(GameError::new("game_over", "the race is over"));
+            }
+            if state.total + step > GOAL {
+                return Err(GameError::new("overshoot", format!("{} would pass {}", step, GOAL)));
+            }
+            state.total += step;
+            state.moves += 1;
+            Ok(())
+        }
+
+        fn outcome(state: &RaceState) -> Outcome {
+            if state.total < GOAL {
+                Outcome::Ongoing
+            } else if !state.moves.is_multiple_of(2) {
+                Outcome::Winner("player1".to_string())
+            } else {
+                Outcome::Winner("player2".to_string())
+            }
+        }
+
+        fn current_player(state: &RaceState) -> String {
+            if state.moves.is_multiple_of(2) { "player1" } else { "player2" }.to_string()
+        }
+
+        fn render(state: &RaceState) -> String {
+            format!("total {}", state.total)
+        }
+
+        fn transcript(state: &RaceState) -> String {
+            format!("Race to {}: {} after {} moves", GOAL, state.total, state.moves)
+        }
+    }
+
+    crate::export_game!(Race);
+
+    fn parse_error(json: &str) -> GameError {
+        let error: serde_json::Value = serde_json::from_str(json).unwrap();
+        GameError::new(error["code"].as_str().unwrap(), error["message"].as_str().unwrap())
+    }
+
+    fn error(result: &str) -> GameError {
+        parse_error(result.strip_prefix("ERROR: ").unwrap())
+    }
+
+    fn last_error() -> GameError {
+        parse_error(&take(get_last_error()))
+    }
+
+    #[test]
+    fn the_generated_exports_play_a_game() {
+        assert_eq!(take(get_game_name()), "Race");
+        assert_eq!(take(get_game_description()), "Add 1 or 2; reach 5 to win");
+
+        let mut state = take(get_initial_state());
+        assert_eq!(state, r#"{"total":0,"moves":0}"#);
+        for (move_str, player) in [("2", "player1"), ("2", "player2"), ("1", "player1")] {
+            assert_eq!(take(get_current_player(c(&state).as_ptr())), player);
+            assert_eq!(is_game_over(c(&state).as_ptr()), 0);
+            state = play(apply_move, &state, move_str);
+        }
+
+        assert_eq!(is_game_over(c(&state).as_ptr()), 1);
+        assert_eq!(take(get_winner(c(&state).as_ptr())), "player1");
+        assert_eq!(take(get_valid_moves(c(&state).as_ptr())), "[]");
+        assert_eq!(take(render(c(&state).as_ptr())), "total 5");
+        assert_eq!(take(log_transcript(c(&state).as_ptr())), "Race to 5: 5 after 3 moves");
+    }
+
+    #[test]
+    fn inputs_can_be_written_into_blocks_from_the_module() {
+        let state = take(get_initial_state());
+        let block = arena_malloc(state.len() + 1);
+        unsafe {
+            std::ptr::copy_nonoverlapping(state.as_ptr(), block, state.len());
+            block.add(state.len()).write(0);
+        }
+        assert_eq!(take(get_valid_moves(block as *const c_char)), r#"["1","2"]"#);
+        arena_free(block);
+    }
+
+    #[test]
+    fn the_module_reports_its_abi_version_and_capabilities() {
+        assert_eq!(get_abi_version(), crate::ABI_VERSION as i32);
+        assert_eq!(take(get_capabilities()), Race::CAPABILITIES.to_json());
+        assert_eq!(take(get_state_capabilities(c(r#"{"total":3,"moves":2}"#).as_ptr())), Race::CAPABILITIES.to_json());
+        assert!(take(get_state_capabilities(c(r#"{"total":9,"moves":2}"#).as_ptr())).starts_with("ERROR:"));
+    }
+
+    #[test]
+    fn an_unreadable_state_is_an_error_and_never_a_fresh_game() {
+        for (state, message) in [
+            ("", "state is not valid JSON for this game"),
+            (r#"{"total":"three"}"#, "state is not valid JSON for this game"),
+            (r#"{"total":9,"moves":4}"#, "total cannot pass 5"),
+        ] {
+            for result in [
+                take(get_valid_moves(c(state).as_ptr())),
+                take(apply_move(c(state).as_ptr(), c("1").as_ptr())),
+                take(get_turn_info(c(state).as_ptr())),
+            ] {
+                let error = error(&result);
+                assert_eq!(error.code, "invalid_state", "{}", state);
+                assert!(error.message.starts_with(message), "{}", error.message);
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_exports_leave_the_reason_for_get_last_error() {
+        assert_eq!(take(get_last_error()), "");
+        assert_eq!(is_game_over(c("{").as_ptr()), -1);
+
+        assert_eq!(last_error().code, "invalid_state");
+        assert_eq!(take(get_last_error()), "");
+    }
+
+    #[test]
+    fn a_panicking_move_does_not_take_the_module_down() {
+        let state = take(get_initial_state());
+        let result = take(apply_move(c(&state).as_ptr(), c("boom").as_ptr()));
+        let error = error(&result);
+        assert_eq!(error.code, "panic");
+        assert!(error.message.starts_with("the game blew up at "), "{}", error.message);
+
+        let handle = handle_create();
+        assert_eq!(handle_apply_move(handle, c("boom").as_ptr()), -1);
+        assert_eq!(handle_apply_move(handle, c("1").as_ptr()), 0);
+        assert_eq!(take(handle_get_state(handle)), r#"{"total":1,"moves":1}"#);
+        handle_drop(handle);
+    }
+
+    #[test]
+    fn handles_answer_like_the_state_they_hold() {
+        let state = play(apply_move, &play(apply_move, &take(get_initial_state()), "2"), "2");
+        let handle = handle_from_state(c(&state).as_ptr());
+        assert!(handle > 0);
+        assert_eq!(take(handle_get_state(handle)), state);
+        assert_eq!(take(handle_get_valid_moves(handle)), take(get_valid_moves(c(&state).as_ptr())));
+        assert_eq!(take(handle_get_current_player(handle)), "player1");
+        assert_eq!(take(handle_render(handle)), "total 4");
+
+        let copy = handle_clone(handle);
+        assert_eq!(handle_apply_move(copy, c("1").as_ptr()), 0);
+        assert_eq!(handle_is_game_over(copy), 1);
+        assert_eq!(take(handle_get_winner(copy)), "player1");
+        assert_eq!(handle_is_game_over(handle), 0);
+
+        assert_eq!(handle_apply_move(handle, c("2").as_ptr()), -1);
+        assert_eq!(last_error(), GameError::new("overshoot", "2 would pass 5"));
+
+        handle_drop(handle);
+        handle_drop(copy);
+    }
+
+    #[test]
+    fn unknown_handles_and_bad_states_are_refused() {
+        assert_eq!(handle_from_state(c(r#"{"total":9,"moves":4}"#).as_ptr()), -1);
+        assert_eq!(last_error(), GameError::new("invalid_state", "total cannot pass 5"));
+
+        let handle = handle_create();
+        handle_drop(handle);
+        assert_eq!(error(&take(handle_get_state(handle))).code, "invalid_handle");
+        assert_eq!(handle_apply_move(handle, c("1").as_ptr()), -1);
+        assert_eq!(handle_clone(handle), -1);
+        assert_eq!(handle_is_game_over(handle), -1);
+    }
+
+    #[test]
+    fn turn_info_answers_every_per_turn_query_at_once() {
+        let ongoing = play(apply_move, &take(get_initial_state()), "2");
+        let info: serde_json::Value = serde_json::from_str(&take(get_turn_info(c(&ongoing).as_ptr()))).unwrap();
+        assert_eq!(
+            info,
+            serde_json::json!({
+                "valid_moves": ["1", "2"],
+                "game_over": false,
+                "winner": "",
+                "current_player": "player2",
+                "render": "total 2",
+            })
+        );
+
+        let finished = play(apply_move, &play(apply_move, &ongoing, "2"), "1");
+        let info: serde_json::Value = serde_json::from_str(&take(get_turn_info(c(&finished).as_ptr()))).unwrap();
+        assert_eq!(info["valid_moves"], serde_json::json!([]));
+        assert_eq!(info["game_over"], true);
+        assert_eq!(info["winner"], "player1");
+
+        let handle = handle_from_state(c(&finished).as_ptr());
+        assert_eq!(take(handle_get_turn_info(handle)), take(get_turn_info(c(&finished).as_ptr())));
+        handle_drop(handle);
+    }
+
+    /// The bytes of a length-prefixed buffer from `encode_state`, freed.
+    #[cfg(feature = "binary-state")]
+    fn take_buffer(buffer: *mut u8) -> Vec<u8> {
+        assert!(!buffer.is_null());
+        let bytes = unsafe {
+            let len = u32::from_le_bytes(std::slice::from_raw_parts(buffer, 4).try_into().unwrap());
+            std::slice::from_raw_parts(buffer.add(4), len as usize).to_vec()
+        };
+        arena_free(buffer);
+        bytes
+    }
+
+    #[cfg(feature = "binary-state")]
+    #[test]
+    fn binary_states_round_trip() {
+        assert_eq!(take(get_state_formats()), r#"["json","msgpack"]"#);
+
+        let state = play(apply_move, &take(get_initial_state()), "2");
+        let bytes = take_buffer(encode_state(c(&state).as_ptr()));
+        assert!(bytes.len() < state.len());
+        assert_eq!(take(decode_state(bytes.as_ptr(), bytes.len())), state);
+
+        let result = take(decode_state([0xc1].as_ptr(), 1));
+        assert_eq!(error(&result).code, "invalid_state");
+        let result = take(decode_state(bytes.as_ptr(), bytes.len() - 1));
+        assert_eq!(error(&result).code, "invalid_state");
+    }
+
+    #[cfg(feature = "binary-state")]
+    #[test]
+    fn decoded_states_are_checked_like_json_ones() {
+        let bytes = rmp_serde::to_vec(&RaceState { total: 9, moves: 4 }).unwrap();
+        let result = take(decode_state(bytes.as_ptr(), bytes.len()));
+        assert_eq!(error(&result), GameError::new("invalid_state", "total cannot pass 5"));
+    }
+
+    #[cfg(not(feature = "binary-state"))]
+    #[test]
+    fn without_the_feature_only_json_is_offered() {
+        assert_eq!(take(get_state_formats()), r#"["json"]"#);
+
+        let state = take(get_initial_state());
+        assert!(encode_state(c(&state).as_ptr()).is_null());
+        assert_eq!(last_error().code, "unsupported_format");
+        assert_eq!(error(&take(decode_state([0x90].as_ptr(), 1))).code, "unsupported_format");
+    }
+
+    #[test]
+    fn a_state_that_cannot_be_read_is_not_encoded() {
+        assert!(encode_state(c("{").as_ptr()).is_null());
+        assert_eq!(last_error().code, "invalid_state");
+    }
+
+    #[test]
+    fn a_rejected_move_is_an_error_result() {
+        let state = play(apply_move, &take(get_initial_state()), "2");
+        let result = take(apply_move(c(&state).as_ptr(), c("3").as_ptr()));
+        assert_eq!(error(&result), GameError::new("malformed", "'3' is not 1 or 2"));
+    }
+
+    #[test]
+    fn apply_moves_plays_in_order_and_names_the_move_it_stopped_at() {
+        let start = take(get_initial_state());
+        let result = take(apply_moves(c(&start).as_ptr(), c(r#"["2", "2", "1"]"#).as_ptr()));
+        assert_eq!(result, r#"{"total":5,"moves":3}"#);
+
+        let result = take(apply_moves(c(&start).as_ptr(), c(r#"["2", "2", "2"]"#).as_ptr()));
+        assert_eq!(error(&result), GameError::new("overshoot", "move 2 (\"2\"): 2 would pass 5"));
+
+        let result = take(apply_moves(c(&start).as_ptr(), c("2, 2").as_ptr()));
+        assert_eq!(error(&result).code, "invalid_moves");
+    }
+}
@@ -0,0 +1,246 @@
+//! Moving strings and states across the C ABI. Every string in either
+//! direction is NUL-terminated UTF-8.
+//!
+//! Memory is owned by the side that reads it last: the host allocates its
+//! inputs with [`alloc`] and releases them with [`free`] once the call
+//! returns, and releases every string a call returns with
+//! [`dealloc_string`]. Binary data travels as a block from [`alloc`]
+//! starting with its length as a little-endian `u32` (see [`to_buffer`]),
+//! which the host also releases with [`free`].
+
+use std::alloc::{self, Layout};
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::os::raw::c_char;
+use std::ptr;
+
+use crate::binary;
+use crate::guard::set_last_error;
+use crate::log::Level;
+use crate::{host_log, Game, GameError};
+
+/// Hands a string to the host. The host reads it up to the NUL byte, so
+/// any NUL inside the string is dropped rather than cutting it short.
+pub fn to_c_string(s: String) -> *mut c_char {
+    let c_string = CString::new(s).unwrap_or_else(|error| {
+        let mut bytes = error.into_vec();
+        bytes.retain(|&byte| byte != 0);
+        CString::new(bytes).unwrap_or_default()
+    });
+    c_string.into_raw()
+}
+
+/// Frees a string returned by any export. Null is ignored.
+// Only pointers produced by `to_c_string` may be passed back here.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn dealloc_string(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        unsafe {
+            drop(CString::from_raw(ptr));
+        }
+    }
+}
+
+/// Bytes in front of each `alloc` block recording its size, so `free` does
+/// not need to be told it.
+const HEADER: usize = mem::size_of::<usize>();
+
+fn block_layout(size: usize) -> Option<Layout> {
+    Layout::from_size_align(size.checked_add(HEADER)?, mem::align_of::<usize>()).ok()
+}
+
+/// Allocates `size` bytes for the host to write an input into. Returns
+/// null if the allocation fails.
+pub fn alloc(size: usize) -> *mut u8 {
+    let layout = match block_layout(size) {
+        Some(layout) => layout,
+        None => return ptr::null_mut(),
+    };
+    unsafe {
+        let block = alloc::alloc(layout);
+        if block.is_null() {
+            return block;
+        }
+        (block as *mut usize).write(size);
+        block.add(HEADER)
+    }
+}
+
+/// Releases a block from [`alloc`]. Null is ignored.
+// Only pointers produced by `alloc` may be passed back here.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn free(ptr: *mut u8) {
+    if ptr.is_null() {
+        return;
+    }
+    unsafe {
+        let block = ptr.sub(HEADER);
+        let size = (block as *const usize).read();
+        if let Some(layout) = block_layout(size) {
+            alloc::dealloc(block, layout);
+        }
+    }
+}
+
+/// Hands the host a copy of `bytes`, behind a little-endian `u32` length,
+/// in a block it releases with [`free`]. Null if the allocation fails.
+pub fn to_buffer(bytes: &[u8]) -> *mut u8 {
+    let len = match u32::try_from(bytes.len()) {
+        Ok(len) => len,
+        Err(_) => return ptr::null_mut(),
+    };
+    let buffer = alloc(bytes.len() + 4);
+    if !buffer.is_null() {
+        unsafe {
+            ptr::copy_nonoverlapping(len.to_le_bytes().as_ptr(), buffer, 4);
+            ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.add(4), bytes.len());
+        }
+    }
+    buffer
+}
+
+/// Copies `len` bytes the host passed in. A null pointer reads as no bytes.
+// The host owns the pointer; as with strings the ABI trusts it.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn from_bytes(ptr: *const u8, len: usize) -> Vec<u8> {
+    if ptr.is_null() {
+        return Vec::new();
+    }
+    unsafe { std::slice::from_raw_parts(ptr, len).to_vec() }
+}
+
+/// Copies a string the host passed in. Invalid UTF-8 is replaced rather
+/// than rejected, and a null pointer reads as an empty string.
+// The host owns the pointer; this is the one place the ABI trusts it.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn from_c_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+/// Hands the host either a result or the `ERROR: {...}` form of an error.
+pub fn to_c_result(result: Result<String, GameError>) -> *mut c_char {
+    match result {
+        Ok(s) => to_c_string(s),
+        Err(error) => to_c_string(error.to_result()),
+    }
+}
+
+/// Reads the state the host passed in. JSON that does not parse, or that
+/// [`Game::check_state`] refuses, is an `invalid_state` error carrying the
+/// reason; it is never replaced by a fresh game. The error is also kept for
+/// `get_last_error`, since exports returning a number can only say -1.
+pub fn read_state<G: Game>(state_ptr: *const c_char) -> Result<G::State, GameError> {
+    checked_state::<G>(
+        serde_json::from_str(&from_c_string(state_ptr))
+            .map_err(|error| GameError::new("invalid_state", format!("state is not valid JSON for this game: {}", error))),
+    )
+}
+
+/// Reads a state the host passed in as binary (see [`binary`](crate::binary)),
+/// with the same checks as [`read_state`].
+pub fn read_binary_state<G: Game>(ptr: *const u8, len: usize) -> Result<G::State, GameError> {
+    checked_state::<G>(binary::decode::<G>(&from_bytes(ptr, len)))
+}
+
+fn checked_state<G: Game>(result: Result<G::State, GameError>) -> Result<G::State, GameError> {
+    let result = result.and_then(|state| match G::check_state(&state) {
+        Ok(()) => Ok(state),
+        Err(message) => Err(GameError::new("invalid_state", message)),
+    });
+    if let Err(error) = &result {
+        host_log!(Level::Warn, "{}", error.message);
+        set_last_error(error);
+    }
+    result
+}
+
+/// Serializes a state for the host.
+pub fn write_state<G: Game>(state: &G::State) -> *mut c_char {
+    to_c_result(to_json(state))
+}
+
+/// JSON for a value an export returns. Serializing can only fail for a
+/// type serde_json cannot represent (a map with non-string keys, say);
+/// that comes back as an error rather than a trap.
+pub fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<String, GameError> {
+    serde_json::to_string(value).map_err(|error| GameError::new("internal", format!("result cannot be serialized: {}", error)))
+}
+
+/// Calling exports from tests the way the host does. Game crates get it
+/// through the `test-util` feature in their dev-dependencies.
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util {
+    use std::ffi::CString;
+    use std::os::raw::c_char;
+
+    use super::{dealloc_string, from_c_string};
+
+    /// The string an export returned, released as the host releases it.
+    pub fn take(ptr: *mut c_char) -> String {
+        let result = from_c_string(ptr);
+        dealloc_string(ptr);
+        result
+    }
+
+    pub fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    /// Plays `move_str` through a game's `apply_move` export, failing the
+    /// test if the move is rejected.
+    pub fn play(apply_move: extern "C" fn(*const c_char, *const c_char) -> *mut c_char, state: &str, move_str: &str) -> String {
+        let next = take(apply_move(c(state).as_ptr(), c(move_str).as_ptr()));
+        assert!(!next.starts_with("ERROR:"), "{} rejected: {}", move_str, next);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::take;
+    use super::*;
+
+    #[test]
+    fn blocks_hold_what_is_written_to_them() {
+        for size in [0, 1, 100, 1 << 16] {
+            let block = alloc(size);
+            assert!(!block.is_null());
+            unsafe {
+                ptr::write_bytes(block, 0xab, size);
+                assert!(std::slice::from_raw_parts(block, size).iter().all(|&byte| byte == 0xab));
+            }
+            free(block);
+        }
+        assert!(alloc(usize::MAX).is_null());
+        free(ptr::null_mut());
+    }
+
+    #[test]
+    fn buffers_start_with_their_length() {
+        let buffer = to_buffer(b"state");
+        unsafe {
+            assert_eq!(std::slice::from_raw_parts(buffer, 4), 5u32.to_le_bytes());
+            assert_eq!(std::slice::from_raw_parts(buffer.add(4), 5), b"state");
+        }
+        free(buffer);
+    }
+
+    #[test]
+    fn strings_lose_inner_nuls_instead_of_being_cut_short() {
+        assert_eq!(take(to_c_string("before\0after".to_string())), "beforeafter");
+        assert_eq!(take(to_c_string(String::new())), "");
+        dealloc_string(ptr::null_mut());
+    }
+
+    #[test]
+    fn null_inputs_read_as_empty() {
+        assert_eq!(from_c_string(ptr::null()), "");
+        assert_eq!(from_bytes(ptr::null(), 10), Vec::<u8>::new());
+        assert_eq!(from_c_string(c"caf\xc3\xa9 \xff".as_ptr()), "café \u{fffd}");
+    }
+}
@@ -0,0 +1,126 @@
+//! Keeps a panic inside a game from taking the whole module down, and
+//! remembers what went wrong for `get_last_error`.
+//!
+//! Every export runs inside [`guard`]. Where panics unwind (native builds,
+//! or wasm built with `panic = "unwind"`), a panic becomes an `ERROR:`
+//! result, -1 from exports returning a number, or null from exports
+//! returning a buffer. On the default wasm32 target panics abort, so the
+//! host sees a trap, but the panic hook has already recorded the message
+//! and location for `get_last_error`.
+
+use std::cell::RefCell;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Once;
+
+use crate::ffi::to_c_string;
+use crate::log::Level;
+use crate::{host_log, GameError};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<GameError>> = const { RefCell::new(None) };
+}
+
+/// Records the error `get_last_error` reports next.
+pub fn set_last_error(error: &GameError) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(error.clone()));
+}
+
+/// The last recorded error, cleared so it is reported only once.
+pub fn take_last_error() -> Option<GameError> {
+    LAST_ERROR.with(|last| last.borrow_mut().take())
+}
+
+/// What an export hands back when its body panicked.
+pub trait PanicResult {
+    fn from_error(error: &GameError) -> Self;
+}
+
+impl PanicResult for *mut c_char {
+    fn from_error(error: &GameError) -> Self {
+        to_c_string(error.to_result())
+    }
+}
+
+impl PanicResult for *mut u8 {
+    fn from_error(_error: &GameError) -> Self {
+        ptr::null_mut()
+    }
+}
+
+impl PanicResult for i32 {
+    fn from_error(_error: &GameError) -> Self {
+        -1
+    }
+}
+
+fn install_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = match (info.payload().downcast_ref::<&str>(), info.payload().downcast_ref::<String>()) {
+                (Some(message), _) => message.to_string(),
+                (_, Some(message)) => message.clone(),
+                _ => "unknown panic".to_string(),
+            };
+            let message = match info.location() {
+                Some(location) => format!("{} at {}:{}", message, location.file(), location.line()),
+                None => message,
+            };
+            host_log!(Level::Error, "panic: {}", message);
+            set_last_error(&GameError::new("panic", message));
+            previous(info);
+        }));
+    });
+}
+
+/// Runs an export body, turning a panic into the export's error result.
+pub fn guard<R: PanicResult>(body: impl FnOnce() -> R) -> R {
+    install_hook();
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(result) => result,
+        Err(_) => {
+            let error = LAST_ERROR
+                .with(|last| last.borrow().clone())
+                .unwrap_or_else(|| GameError::new("panic", "the game module panicked"));
+            R::from_error(&error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::{dealloc_string, from_c_string};
+
+    #[test]
+    fn a_panic_becomes_each_exports_error_result() {
+        let result = guard(|| -> *mut c_char { panic!("string export") });
+        let text = from_c_string(result);
+        dealloc_string(result);
+        assert!(text.starts_with(r#"ERROR: {"code":"panic","message":"string export at "#), "{}", text);
+        assert!(text.contains("guard.rs"), "{}", text);
+
+        assert_eq!(guard(|| -> i32 { panic!("number export") }), -1);
+        assert!(guard(|| -> *mut u8 { panic!("buffer export") }).is_null());
+    }
+
+    #[test]
+    fn the_panic_is_kept_for_get_last_error() {
+        take_last_error();
+        assert_eq!(guard(|| -> i32 { panic!("{} went wrong", 2 + 2) }), -1);
+
+        let error = take_last_error().unwrap();
+        assert_eq!(error.code, "panic");
+        assert!(error.message.starts_with("4 went wrong at "), "{}", error.message);
+        assert_eq!(take_last_error(), None);
+    }
+
+    #[test]
+    fn results_pass_through_untouched() {
+        assert_eq!(guard(|| 7), 7);
+        assert_eq!(take_last_error(), None);
+    }
+}
@@ -0,0 +1,465 @@
+//! Shared plumbing for LLM Arena game modules.
+//!
+//! A game implements [`Game`] and calls [`export_game!`] to get every
+//! export of the standard interface. Game-specific extras are written as
+//! ordinary `#[no_mangle]` functions using the helpers in [`ffi`].
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub mod binary;
+#[doc(hidden)]
+pub mod exports;
+pub mod ffi;
+pub mod guard;
+pub mod log;
+pub mod registry;
+
+/// Version of the export interface generated by [`export_game!`], returned
+/// by `get_abi_version`. Bumped whenever an export changes meaning.
+///
+/// Version 2: exports taking a state reject an unreadable one with an
+/// `invalid_state` error instead of answering for a fresh game, and i32
+/// exports leave the reason for a -1 in `get_last_error`.
+pub const ABI_VERSION: u32 = 2;
+
+/// The optional features a module supports, returned as JSON by
+/// `get_capabilities` so hosts do not have to probe export names.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Capabilities {
+    /// Number of seats at the table.
+    pub players: u32,
+    /// Moves can be rendered in a human notation (`get_move_notation`).
+    pub notation: bool,
+    /// The module can suggest or score moves for the player to move.
+    pub hints: bool,
+    /// The module can grade a finished game move by move.
+    pub analysis: bool,
+    /// Drawn games say why (`get_draw_reason`).
+    pub draw_reasons: bool,
+    /// Games can start from a position or configuration other than the
+    /// default.
+    pub custom_start: bool,
+    /// Players see different parts of the state.
+    pub hidden_info: bool,
+}
+
+impl Capabilities {
+    /// A two-player perfect-information game with no optional extras.
+    pub const BASIC: Capabilities = Capabilities {
+        players: 2,
+        notation: false,
+        hints: false,
+        analysis: false,
+        draw_reasons: false,
+        custom_start: false,
+        hidden_info: false,
+    };
+
+    /// True for games with more than two seats.
+    pub fn multiplayer(&self) -> bool {
+        self.players > 2
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["multiplayer"] = self.multiplayer().into();
+        value.to_string()
+    }
+}
+
+/// Where a game stands.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    Ongoing,
+    Winner(String),
+    Draw,
+}
+
+impl Outcome {
+    pub fn is_over(&self) -> bool {
+        *self != Outcome::Ongoing
+    }
+
+    /// The `get_winner` result: the winning player, "draw", or "" while the
+    /// game goes on.
+    pub fn winner(&self) -> &str {
+        match self {
+            Outcome::Ongoing => "",
+            Outcome::Winner(player) => player,
+            Outcome::Draw => "draw",
+        }
+    }
+}
+
+/// A move or request the game refused. It reaches the host as
+/// `ERROR: {"code": ..., "message": ...}` in place of the normal result,
+/// so the message can be passed back to the agent that caused it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameError {
+    pub code: String,
+    pub message: String,
+}
+
+impl GameError {
+    pub fn new(code: &str, message: impl Into<String>) -> GameError {
+        GameError {
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub fn to_result(&self) -> String {
+        format!("ERROR: {}", serde_json::json!({ "code": self.code, "message": self.message }))
+    }
+}
+
+/// The rules of a turn-based game. States are plain data passed to and from
+/// the host as JSON, so every method takes the state it works on.
+pub trait Game {
+    type State: Serialize + DeserializeOwned + Clone + 'static;
+
+    const NAME: &'static str;
+    const DESCRIPTION: &'static str;
+    const CAPABILITIES: Capabilities = Capabilities::BASIC;
+
+    fn initial_state() -> Self::State;
+
+    /// Rejects a state that parses but cannot be played, such as a board
+    /// of the wrong shape. Every export taking a state runs it first and
+    /// returns an `invalid_state` error with the message.
+    fn check_state(_state: &Self::State) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// The capabilities that hold for this state, reported by
+    /// `get_state_capabilities`. A game whose hints only cover some board
+    /// sizes turns them off here for the others. Defaults to
+    /// `CAPABILITIES`.
+    fn capabilities(_state: &Self::State) -> Capabilities {
+        Self::CAPABILITIES
+    }
+
+    /// Every move the player to move may make, in the notation `apply`
+    /// accepts. Empty once the game is over.
+    fn legal_moves(state: &Self::State) -> Vec<String>;
+
+    /// Plays `move_str` for the player to move. On error `state` is left
+    /// as it was.
+    fn apply(state: &mut Self::State, move_str: &str) -> Result<(), GameError>;
+
+    fn outcome(state: &Self::State) -> Outcome;
+
+    /// "player1", "player2", ...
+    fn current_player(state: &Self::State) -> String;
+
+    fn render(state: &Self::State) -> String;
+
+    /// What `player` is allowed to know about the state, as JSON. Games
+    /// with hidden information override this; by default every player sees
+    /// the whole state.
+    fn observation(state: &Self::State, _player: &str) -> serde_json::Value {
+        serde_json::to_value(state).unwrap_or_default()
+    }
+
+    /// `render` as seen from `player`'s seat. Defaults to `render`.
+    fn render_for(state: &Self::State, _player: &str) -> String {
+        Self::render(state)
+    }
+
+    /// A plain-text log of the game for the transcript panel.
+    fn transcript(state: &Self::State) -> String;
+}
+
+/// Generates the standard `#[no_mangle] extern "C"` exports for a type
+/// implementing [`Game`], each run inside [`guard::guard`], the `handle_*`
+/// exports working on states kept in the [`registry`], the state format
+/// exports from [`binary`], plus `get_last_error`, `set_log_level` and the
+/// memory exports the host uses to pass strings in and release the ones it
+/// gets back. `malloc` and `free` are
+/// only exported on wasm32, where they do not clash with libc:
+///
+/// ```ignore
+/// struct TicTacToe;
+/// impl arena_game_sdk::Game for TicTacToe { ... }
+/// arena_game_sdk::export_game!(TicTacToe);
+/// ```
+#[macro_export]
+macro_rules! export_game {
+    ($game:ty) => {
+        #[no_mangle]
+        pub extern "C" fn get_initial_state() -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_initial_state::<$game>())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_valid_moves(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_valid_moves::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn apply_move(
+            state_ptr: *const ::std::os::raw::c_char,
+            move_ptr: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::apply_move::<$game>(state_ptr, move_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn apply_moves(
+            state_ptr: *const ::std::os::raw::c_char,
+            moves_ptr: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::apply_moves::<$game>(state_ptr, moves_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_turn_info(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_turn_info::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn is_game_over(state_ptr: *const ::std::os::raw::c_char) -> i32 {
+            $crate::guard::guard(|| $crate::exports::is_game_over::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_winner(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_winner::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn render(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::render::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_observation(
+            state_ptr: *const ::std::os::raw::c_char,
+            player_ptr: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_observation::<$game>(state_ptr, player_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn render_for(
+            state_ptr: *const ::std::os::raw::c_char,
+            player_ptr: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::render_for::<$game>(state_ptr, player_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_game_name() -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_game_name::<$game>())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_current_player(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_current_player::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_game_description() -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_game_description::<$game>())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn log_transcript(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::log_transcript::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_abi_version() -> i32 {
+            $crate::ABI_VERSION as i32
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_capabilities() -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_capabilities::<$game>())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_state_capabilities(state_ptr: *const ::std::os::raw::c_char) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_state_capabilities::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_state_formats() -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_state_formats())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn encode_state(state_ptr: *const ::std::os::raw::c_char) -> *mut u8 {
+            $crate::guard::guard(|| $crate::exports::encode_state::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn decode_state(ptr: *const u8, len: usize) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::decode_state::<$game>(ptr, len))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_create() -> i32 {
+            $crate::guard::guard(|| $crate::exports::handle_create::<$game>())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_from_state(state_ptr: *const ::std::os::raw::c_char) -> i32 {
+            $crate::guard::guard(|| $crate::exports::handle_from_state::<$game>(state_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_clone(handle: i32) -> i32 {
+            $crate::guard::guard(|| $crate::exports::handle_clone::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_apply_move(handle: i32, move_ptr: *const ::std::os::raw::c_char) -> i32 {
+            $crate::guard::guard(|| $crate::exports::handle_apply_move::<$game>(handle, move_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_get_state(handle: i32) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_get_state::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_get_valid_moves(handle: i32) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_get_valid_moves::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_is_game_over(handle: i32) -> i32 {
+            $crate::guard::guard(|| $crate::exports::handle_is_game_over::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_get_winner(handle: i32) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_get_winner::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_get_current_player(handle: i32) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_get_current_player::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_render(handle: i32) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_render::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_get_turn_info(handle: i32) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_get_turn_info::<$game>(handle))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_get_observation(
+            handle: i32,
+            player_ptr: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_get_observation::<$game>(handle, player_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_render_for(
+            handle: i32,
+            player_ptr: *const ::std::os::raw::c_char,
+        ) -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::handle_render_for::<$game>(handle, player_ptr))
+        }
+
+        #[no_mangle]
+        pub extern "C" fn handle_drop(handle: i32) {
+            $crate::exports::handle_drop(handle)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn get_last_error() -> *mut ::std::os::raw::c_char {
+            $crate::exports::get_last_error()
+        }
+
+        #[no_mangle]
+        pub extern "C" fn set_log_level(level: i32) {
+            $crate::log::set_max_level(level)
+        }
+
+        #[cfg_attr(target_arch = "wasm32", export_name = "malloc")]
+        pub extern "C" fn arena_malloc(size: usize) -> *mut u8 {
+            $crate::ffi::alloc(size)
+        }
+
+        #[cfg_attr(target_arch = "wasm32", export_name = "free")]
+        pub extern "C" fn arena_free(ptr: *mut u8) {
+            $crate::ffi::free(ptr)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn dealloc_string(ptr: *mut ::std::os::raw::c_char) {
+            $crate::ffi::dealloc_string(ptr)
+        }
+    };
+}
+
+/// Compiles a game's metadata.json (name, description, `aiPrompts`, ...)
+/// into the module, so an uploaded .wasm describes itself without a
+/// separate metadata file. On wasm32 the bytes go in the `llm_arena_metadata`
+/// custom section, which hosts can read before instantiating, and
+/// `get_metadata()` returns them at runtime. The path is relative to the
+/// file invoking the macro:
+///
+/// ```ignore
+/// arena_game_sdk::embed_metadata!("../metadata.json");
+/// ```
+#[macro_export]
+macro_rules! embed_metadata {
+    ($path:literal) => {
+        #[cfg_attr(target_arch = "wasm32", link_section = "llm_arena_metadata")]
+        #[used]
+        static ARENA_METADATA: [u8; include_bytes!($path).len()] = *include_bytes!($path);
+
+        #[no_mangle]
+        pub extern "C" fn get_metadata() -> *mut ::std::os::raw::c_char {
+            $crate::guard::guard(|| $crate::exports::get_metadata(&ARENA_METADATA))
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(capabilities: Capabilities) -> serde_json::Value {
+        serde_json::from_str(&capabilities.to_json()).unwrap()
+    }
+
+    #[test]
+    fn capabilities_list_every_feature_and_whether_the_game_is_multiplayer() {
+        assert_eq!(
+            json(Capabilities::BASIC),
+            serde_json::json!({
+                "players": 2,
+                "notation": false,
+                "hints": false,
+                "analysis": false,
+                "draw_reasons": false,
+                "custom_start": false,
+                "hidden_info": false,
+                "multiplayer": false,
+            })
+        );
+
+        let table = Capabilities { players: 4, hidden_info: true, ..Capabilities::BASIC };
+        assert_eq!(json(table)["multiplayer"], true);
+        assert_eq!(json(table)["hidden_info"], true);
+    }
+
+    #[test]
+    fn outcomes_name_the_winner() {
+        assert_eq!(Outcome::Ongoing.winner(), "");
+        assert!(!Outcome::Ongoing.is_over());
+        assert_eq!(Outcome::Draw.winner(), "draw");
+        assert_eq!(Outcome::Winner("player3".to_string()).winner(), "player3");
+    }
+}
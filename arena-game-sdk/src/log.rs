@@ -0,0 +1,85 @@
+//! Diagnostics for the arena's transcript panel.
+//!
+//! With the `host-log` feature, a wasm32 build imports
+//! `env.host_log(level, ptr, len)` and passes it every message at or below
+//! the level set through `set_log_level`. The message is `len` bytes of
+//! UTF-8 at `ptr`, valid only for the duration of the call. Without the
+//! feature nothing is imported and messages are never formatted.
+
+use std::sync::atomic::{AtomicI32, Ordering};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(i32)]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+}
+
+static MAX_LEVEL: AtomicI32 = AtomicI32::new(Level::Info as i32);
+
+/// Sets the most verbose level passed to the host: 1 (errors only) to 4
+/// (debug). 0 turns logging off.
+pub fn set_max_level(level: i32) {
+    MAX_LEVEL.store(level.clamp(0, Level::Debug as i32), Ordering::Relaxed);
+}
+
+/// Whether a message at `level` would reach the host.
+pub fn enabled(level: Level) -> bool {
+    cfg!(all(feature = "host-log", target_arch = "wasm32")) && level as i32 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+#[cfg(all(feature = "host-log", target_arch = "wasm32"))]
+#[link(wasm_import_module = "env")]
+extern "C" {
+    fn host_log(level: i32, ptr: *const u8, len: usize);
+}
+
+/// Sends `message` to the host. Prefer [`host_log!`](crate::host_log),
+/// which skips formatting when the level is filtered out.
+#[cfg(all(feature = "host-log", target_arch = "wasm32"))]
+pub fn write(level: Level, message: &str) {
+    if enabled(level) {
+        unsafe { host_log(level as i32, message.as_ptr(), message.len()) }
+    }
+}
+
+#[cfg(not(all(feature = "host-log", target_arch = "wasm32")))]
+pub fn write(_level: Level, _message: &str) {}
+
+/// Formats and sends a message to the host if `level` is enabled:
+///
+/// ```ignore
+/// host_log!(Level::Debug, "{} legal moves", moves.len());
+/// ```
+#[macro_export]
+macro_rules! host_log {
+    ($level:expr, $($arg:tt)+) => {
+        if $crate::log::enabled($level) {
+            $crate::log::write($level, &format!($($arg)+));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_clamped_and_nothing_is_sent_off_wasm() {
+        set_max_level(9);
+        assert_eq!(MAX_LEVEL.load(Ordering::Relaxed), Level::Debug as i32);
+        set_max_level(-3);
+        assert_eq!(MAX_LEVEL.load(Ordering::Relaxed), 0);
+        set_max_level(Level::Info as i32);
+
+        assert!(!enabled(Level::Error));
+        let mut formatted = false;
+        host_log!(Level::Error, "{}", {
+            formatted = true;
+            "never formatted"
+        });
+        assert!(!formatted);
+    }
+}
@@ -0,0 +1,195 @@
+//! Game states kept inside the module and referred to by integer handles,
+//! so a host running many queries on the same position does not send the
+//! state back and forth as JSON on every call.
+//!
+//! Handles are positive and never reused within an instance. An unknown
+//! handle is an `invalid_handle` error.
+//!
+//! The registry is never borrowed while game code runs: queries work on a
+//! shared pointer taken out of it, and moves on the state itself, lifted
+//! out of the registry for the move and put back afterwards. A panic traps
+//! on wasm32 without unwinding, and a borrow held at that point would fail
+//! every later `handle_*` call in the instance.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::{Game, GameError};
+
+#[derive(Default)]
+struct Registry {
+    last: i32,
+    states: HashMap<i32, Rc<dyn Any>>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+fn unknown(handle: i32) -> GameError {
+    GameError::new("invalid_handle", format!("no state with handle {}", handle))
+}
+
+/// The state behind `handle`, without keeping the registry borrowed.
+fn get<G: Game>(handle: i32) -> Result<Rc<G::State>, GameError> {
+    REGISTRY
+        .with(|registry| registry.borrow().states.get(&handle).cloned())
+        .and_then(|state| state.downcast::<G::State>().ok())
+        .ok_or_else(|| unknown(handle))
+}
+
+/// Stores a state and returns its handle.
+pub fn insert<G: Game>(state: G::State) -> i32 {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        registry.last += 1;
+        let handle = registry.last;
+        registry.states.insert(handle, Rc::new(state));
+        handle
+    })
+}
+
+/// Runs `f` on the state behind `handle`.
+pub fn with_state<G: Game, R>(handle: i32, f: impl FnOnce(&G::State) -> R) -> Result<R, GameError> {
+    get::<G>(handle).map(|state| f(&state))
+}
+
+/// A state lifted out of the registry by `with_state_mut`. Dropping it
+/// puts the state back, so it also returns if the game code panics and the
+/// build unwinds.
+struct Lifted<T: Any> {
+    handle: i32,
+    state: Rc<T>,
+}
+
+impl<T: Any> Drop for Lifted<T> {
+    fn drop(&mut self) {
+        let state: Rc<dyn Any> = self.state.clone();
+        REGISTRY.with(|registry| registry.borrow_mut().states.insert(self.handle, state));
+    }
+}
+
+/// Runs `f` on the state behind `handle` in place. The state is only
+/// copied if a query still holds it, which cannot happen between exports.
+/// A rejected move needs no rollback: `Game::apply` leaves the state as it
+/// was when it returns an error.
+pub fn with_state_mut<G: Game, R>(handle: i32, f: impl FnOnce(&mut G::State) -> R) -> Result<R, GameError> {
+    let state = REGISTRY.with(|registry| registry.borrow_mut().states.remove(&handle)).ok_or_else(|| unknown(handle))?;
+    let state = match state.downcast::<G::State>() {
+        Ok(state) => state,
+        Err(other) => {
+            REGISTRY.with(|registry| registry.borrow_mut().states.insert(handle, other));
+            return Err(unknown(handle));
+        }
+    };
+    let mut lifted = Lifted { handle, state };
+    Ok(f(Rc::make_mut(&mut lifted.state)))
+}
+
+/// Frees the state behind `handle`. Returns false if there was none.
+pub fn remove(handle: i32) -> bool {
+    REGISTRY.with(|registry| registry.borrow_mut().states.remove(&handle).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Outcome;
+
+    /// Counts the moves played, checking on every call that the registry
+    /// is free for other calls to use.
+    struct Counter;
+
+    fn registry_is_free() -> bool {
+        REGISTRY.with(|registry| registry.try_borrow_mut().is_ok())
+    }
+
+    impl Game for Counter {
+        type State = u32;
+
+        const NAME: &'static str = "Counter";
+        const DESCRIPTION: &'static str = "Counts moves";
+
+        fn initial_state() -> u32 {
+            0
+        }
+
+        fn legal_moves(_state: &u32) -> Vec<String> {
+            assert!(registry_is_free());
+            vec!["up".to_string()]
+        }
+
+        fn apply(state: &mut u32, move_str: &str) -> Result<(), GameError> {
+            assert!(registry_is_free());
+            match move_str {
+                "up" => {
+                    *state += 1;
+                    Ok(())
+                }
+                _ => Err(GameError::new("malformed", "only up")),
+            }
+        }
+
+        fn outcome(_state: &u32) -> Outcome {
+            Outcome::Ongoing
+        }
+
+        fn current_player(_state: &u32) -> String {
+            "player1".to_string()
+        }
+
+        fn render(state: &u32) -> String {
+            state.to_string()
+        }
+
+        fn transcript(state: &u32) -> String {
+            state.to_string()
+        }
+    }
+
+    #[test]
+    fn game_code_runs_without_the_registry_borrowed() {
+        let handle = insert::<Counter>(0);
+        assert_eq!(with_state::<Counter, _>(handle, Counter::legal_moves).unwrap(), ["up"]);
+        with_state_mut::<Counter, _>(handle, |state| Counter::apply(state, "up")).unwrap().unwrap();
+        assert_eq!(with_state::<Counter, _>(handle, |state| *state).unwrap(), 1);
+        remove(handle);
+    }
+
+    #[test]
+    fn moves_are_played_on_the_stored_state() {
+        let handle = insert::<Counter>(0);
+        let stored = Rc::as_ptr(&get::<Counter>(handle).unwrap());
+        with_state_mut::<Counter, _>(handle, |state| Counter::apply(state, "up")).unwrap().unwrap();
+        assert_eq!(Rc::as_ptr(&get::<Counter>(handle).unwrap()), stored);
+
+        let error = with_state_mut::<Counter, _>(handle, |state| Counter::apply(state, "down")).unwrap().unwrap_err();
+        assert_eq!(error.code, "malformed");
+        assert_eq!(with_state::<Counter, _>(handle, |state| *state).unwrap(), 1);
+        remove(handle);
+    }
+
+    #[test]
+    fn handles_are_not_reused() {
+        let first = insert::<Counter>(0);
+        assert!(remove(first));
+        let second = insert::<Counter>(0);
+        assert!(second > first);
+        assert!(!remove(first));
+        assert_eq!(with_state::<Counter, _>(first, |state| *state).unwrap_err().code, "invalid_handle");
+        remove(second);
+    }
+
+    #[test]
+    fn moves_change_only_their_own_handle() {
+        let first = insert::<Counter>(0);
+        let second = insert::<Counter>(5);
+        with_state_mut::<Counter, _>(first, |state| Counter::apply(state, "up")).unwrap().unwrap();
+        assert_eq!(with_state::<Counter, _>(first, |state| *state).unwrap(), 1);
+        assert_eq!(with_state::<Counter, _>(second, |state| *state).unwrap(), 5);
+        remove(first);
+        remove(second);
+    }
+}
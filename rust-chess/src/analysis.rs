@@ -0,0 +1,191 @@
+use chess::{Board, ChessMove, Color};
+use serde::Serialize;
+
+use crate::engine::{self, Limits};
+use crate::{resets_halfmove_clock, san};
+
+/// Mate scores are capped to this many centipawns before losses are
+/// computed, so missing a mate in 3 for a winning ending is not counted as
+/// a loss of thirty thousand.
+const SCORE_CAP: i32 = 1_000;
+
+const INACCURACY: i32 = 50;
+const MISTAKE: i32 = 100;
+const BLUNDER: i32 = 300;
+
+fn classify(cp_loss: i32) -> Option<&'static str> {
+    if cp_loss >= BLUNDER {
+        Some("blunder")
+    } else if cp_loss >= MISTAKE {
+        Some("mistake")
+    } else if cp_loss >= INACCURACY {
+        Some("inaccuracy")
+    } else {
+        None
+    }
+}
+
+fn player_for(color: Color) -> &'static str {
+    if color == Color::White { "player1" } else { "player2" }
+}
+
+#[derive(Serialize)]
+pub struct MoveReport {
+    pub ply: usize,
+    #[serde(rename = "move")]
+    pub uci: String,
+    pub san: String,
+    pub player: &'static str,
+    pub best_move: String,
+    pub best_san: String,
+    /// Centipawns from the mover's point of view, capped at `SCORE_CAP`.
+    pub score: i32,
+    pub best_score: i32,
+    pub cp_loss: i32,
+    pub classification: Option<&'static str>,
+}
+
+#[derive(Serialize, Default)]
+pub struct PlayerSummary {
+    pub moves: usize,
+    pub average_cp_loss: f64,
+    pub inaccuracies: usize,
+    pub mistakes: usize,
+    pub blunders: usize,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub depth: u32,
+    pub moves: Vec<MoveReport>,
+    pub player1: PlayerSummary,
+    pub player2: PlayerSummary,
+}
+
+fn summarize(moves: &[MoveReport], player: &str) -> PlayerSummary {
+    let own: Vec<&MoveReport> = moves.iter().filter(|report| report.player == player).collect();
+    if own.is_empty() {
+        return PlayerSummary::default();
+    }
+
+    let total: i32 = own.iter().map(|report| report.cp_loss).sum();
+    let count = |label: &str| own.iter().filter(|report| report.classification == Some(label)).count();
+
+    PlayerSummary {
+        moves: own.len(),
+        average_cp_loss: (total as f64 / own.len() as f64 * 10.0).round() / 10.0,
+        inaccuracies: count("inaccuracy"),
+        mistakes: count("mistake"),
+        blunders: count("blunder"),
+    }
+}
+
+/// Grades every played move against a full-strength search of the
+/// position it was played in. The loss of a move is how much worse its
+/// score is than the best move's, both taken from the same search.
+pub fn analyze(start: Board, start_halfmove: u32, played: &[ChessMove], limits: Limits) -> Report {
+    let mut board = start;
+    let mut history = vec![board.get_hash()];
+    let mut halfmove_clock = start_halfmove;
+    let mut moves = Vec::with_capacity(played.len());
+    let mut deepest = 0;
+
+    for (i, &chess_move) in played.iter().enumerate() {
+        if let Some(grade) = engine::grade_move(&board, &history, halfmove_clock, limits, chess_move) {
+            deepest = deepest.max(grade.depth);
+            let best_score = grade.best_score.clamp(-SCORE_CAP, SCORE_CAP);
+            let score = grade.score.clamp(-SCORE_CAP, SCORE_CAP);
+            let cp_loss = best_score - score;
+
+            moves.push(MoveReport {
+                ply: i + 1,
+                uci: chess_move.to_string(),
+                san: san::to_san(&board, chess_move),
+                player: player_for(board.side_to_move()),
+                best_move: grade.best_move.to_string(),
+                best_san: san::to_san(&board, grade.best_move),
+                score,
+                best_score,
+                cp_loss,
+                classification: classify(cp_loss),
+            });
+        }
+
+        if resets_halfmove_clock(&board, chess_move) {
+            history.clear();
+            halfmove_clock = 0;
+        } else {
+            halfmove_clock = halfmove_clock.saturating_add(1);
+        }
+        board = board.make_move_new(chess_move);
+        history.push(board.get_hash());
+    }
+
+    Report {
+        depth: deepest,
+        player1: summarize(&moves, "player1"),
+        player2: summarize(&moves, "player2"),
+        moves,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn moves(uci: &[&str]) -> Vec<ChessMove> {
+        uci.iter().map(|m| ChessMove::from_str(m).unwrap()).collect()
+    }
+
+    /// 1.e4 e5 2.Qh5 Nc6 3.Qxe5+, hanging the queen to Nxe5.
+    const HUNG_QUEEN: [&str; 5] = ["e2e4", "e7e5", "d1h5", "b8c6", "h5e5"];
+
+    fn assert_hung_queen_is_a_blunder(limits: Limits) {
+        let report = analyze(Board::default(), 0, &moves(&HUNG_QUEEN), limits);
+        let queen = &report.moves[4];
+        assert_eq!(queen.san, "Qxe5+");
+        assert!(queen.cp_loss >= BLUNDER, "cp_loss {} at depth {}", queen.cp_loss, report.depth);
+        assert_eq!(queen.classification, Some("blunder"));
+        assert_eq!(report.player1.blunders, 1);
+    }
+
+    #[test]
+    fn hung_queen_is_a_blunder_at_a_fixed_depth() {
+        assert_hung_queen_is_a_blunder(Limits::new(4, 0));
+    }
+
+    #[test]
+    fn hung_queen_is_a_blunder_when_the_budget_runs_out() {
+        // Deep enough that every position ends on the node budget, so the
+        // played move is graded from an iteration that did not finish.
+        assert_hung_queen_is_a_blunder(Limits::new(0, 100_000));
+        assert_hung_queen_is_a_blunder(Limits::new(0, 0));
+    }
+
+    #[test]
+    fn the_engine_move_loses_nothing() {
+        let board = Board::from_str("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+        let report = analyze(board, 4, &moves(&["h5f7"]), Limits::new(3, 0));
+        let mate = &report.moves[0];
+        assert_eq!(mate.best_move, "h5f7");
+        assert_eq!((mate.score, mate.best_score, mate.cp_loss), (SCORE_CAP, SCORE_CAP, 0));
+        assert_eq!(mate.classification, None);
+    }
+
+    #[test]
+    fn summary_counts_each_players_moves() {
+        let report = analyze(Board::default(), 0, &moves(&HUNG_QUEEN), Limits::new(2, 0));
+        assert_eq!((report.player1.moves, report.player2.moves), (3, 2));
+        assert_eq!(report.moves.iter().map(|m| m.player).collect::<Vec<_>>(), ["player1", "player2", "player1", "player2", "player1"]);
+    }
+
+    #[test]
+    fn losses_are_tagged_by_size() {
+        assert_eq!(classify(49), None);
+        assert_eq!(classify(50), Some("inaccuracy"));
+        assert_eq!(classify(100), Some("mistake"));
+        assert_eq!(classify(300), Some("blunder"));
+    }
+}
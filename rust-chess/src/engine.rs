@@ -0,0 +1,610 @@
+use chess::{get_rank, BitBoard, Board, BoardStatus, ChessMove, Color, MoveGen, Piece, Rank, EMPTY};
+use std::cmp::Reverse;
+
+use crate::eval;
+
+pub const MAX_DEPTH: u32 = 32;
+pub const MAX_SKILL: u32 = 20;
+
+/// Nodes searched when the caller gives no budget: a fraction of a second
+/// natively, a little more in a browser.
+pub const DEFAULT_NODES: u64 = 250_000;
+
+/// The most nodes any search may visit, so a large budget cannot block the
+/// host for more than a few seconds.
+pub const MAX_NODES: u64 = 5_000_000;
+
+/// "Milliseconds" are a node budget, not a clock. The rate is deliberately
+/// low, so searches usually finish well inside the time asked for.
+const NODES_PER_MILLI: u64 = 200;
+
+const INFINITY: i32 = 32_000;
+pub const MATE: i32 = 30_000;
+const MATE_THRESHOLD: i32 = MATE - 1_000;
+
+const TT_SIZE: usize = 1 << 16;
+
+/// How far and how long to search. The budget is counted in nodes rather
+/// than time: wasm32 has no clock without host imports, and a node budget
+/// means the same request always produces the same move, however fast the
+/// host happens to be.
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub depth: u32,
+    pub max_nodes: u64,
+}
+
+impl Limits {
+    /// A search to `depth` plies that stops after `max_nodes` nodes and
+    /// returns the deepest iteration it completed. A depth of 0 searches as
+    /// deep as the budget allows, and a budget of 0 uses `DEFAULT_NODES`;
+    /// budgets above `MAX_NODES` are capped.
+    pub fn new(depth: u32, max_nodes: u64) -> Limits {
+        Limits {
+            depth: if depth == 0 { MAX_DEPTH } else { depth.min(MAX_DEPTH) },
+            max_nodes: if max_nodes == 0 { DEFAULT_NODES } else { max_nodes.min(MAX_NODES) },
+        }
+    }
+
+    /// The `depth_or_millis` argument of the exports. Values up to
+    /// `MAX_DEPTH` are a depth in plies, searched within `MAX_NODES`; larger
+    /// values are a time budget in milliseconds, converted to nodes at
+    /// `NODES_PER_MILLI` and never timed. 0 uses the default budget.
+    pub fn from_depth_or_millis(depth_or_millis: u32) -> Limits {
+        match depth_or_millis {
+            0 => Limits::new(0, DEFAULT_NODES),
+            depth if depth <= MAX_DEPTH => Limits::new(depth, MAX_NODES),
+            millis => Limits::new(0, millis as u64 * NODES_PER_MILLI),
+        }
+    }
+}
+
+pub struct SearchResult {
+    pub best_move: ChessMove,
+    /// Centipawns from the side to move's point of view.
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+impl SearchResult {
+    /// Moves until mate, positive when the side to move is mating.
+    pub fn mate_in(&self) -> Option<i32> {
+        if self.score.abs() < MATE_THRESHOLD {
+            return None;
+        }
+        let plies = MATE - self.score.abs();
+        let moves = (plies + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+struct TtEntry {
+    hash: u64,
+    depth: u32,
+    score: i32,
+    bound: Bound,
+    best_move: Option<ChessMove>,
+}
+
+/// Mate scores count plies from the root, but a table entry can be reached
+/// at any ply. Entries store them counted from their own node instead.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score + ply as i32
+    } else if score <= -MATE_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score - ply as i32
+    } else if score <= -MATE_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Which root moves are searched with a full window, and so get an exact
+/// score rather than a bound. The best move is always exact.
+#[derive(Clone, Copy)]
+enum Exact {
+    /// Only the best move: the window narrows as usual.
+    Best,
+    /// Every move, for weaker levels that pick among them.
+    All,
+    /// The best move and this one, for grading a played move.
+    With(ChessMove),
+}
+
+impl Exact {
+    fn covers(self, chess_move: ChessMove) -> bool {
+        match self {
+            Exact::Best => false,
+            Exact::All => true,
+            Exact::With(exact_move) => exact_move == chess_move,
+        }
+    }
+}
+
+/// A small deterministic generator, so weakened play is reproducible.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `-amplitude..=amplitude`.
+    fn noise(&mut self, amplitude: i32) -> i32 {
+        if amplitude == 0 {
+            return 0;
+        }
+        (self.next() % (2 * amplitude as u64 + 1)) as i32 - amplitude
+    }
+}
+
+struct Searcher {
+    nodes: u64,
+    max_nodes: u64,
+    aborted: bool,
+    tt: Vec<Option<TtEntry>>,
+    killers: Vec<[Option<ChessMove>; 2]>,
+    /// Hashes of every position since the last irreversible move, game
+    /// history first and then the current search path.
+    path: Vec<u64>,
+}
+
+fn side_eval(board: &Board) -> i32 {
+    let score = eval::evaluate(board);
+    if board.side_to_move() == Color::White { score } else { -score }
+}
+
+fn is_capture(board: &Board, chess_move: ChessMove) -> bool {
+    board.piece_on(chess_move.get_dest()).is_some()
+        || (board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            && chess_move.get_source().get_file() != chess_move.get_dest().get_file())
+}
+
+fn is_irreversible(board: &Board, chess_move: ChessMove) -> bool {
+    board.piece_on(chess_move.get_dest()).is_some() || board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+}
+
+/// Most valuable victim, least valuable attacker.
+fn capture_order(board: &Board, chess_move: ChessMove) -> i32 {
+    let victim = board.piece_on(chess_move.get_dest()).map(eval::piece_value).unwrap_or(eval::PAWN_VALUE);
+    let attacker = board.piece_on(chess_move.get_source()).map(eval::piece_value).unwrap_or(0);
+    victim * 10 - attacker / 10
+}
+
+impl Searcher {
+    fn new(history: &[u64], max_nodes: u64) -> Searcher {
+        Searcher {
+            nodes: 0,
+            max_nodes,
+            aborted: false,
+            tt: vec![None; TT_SIZE],
+            killers: vec![[None; 2]; MAX_DEPTH as usize * 2 + 2],
+            path: history.to_vec(),
+        }
+    }
+
+    fn probe(&self, hash: u64) -> Option<TtEntry> {
+        self.tt[(hash as usize) % TT_SIZE].filter(|entry| entry.hash == hash)
+    }
+
+    fn store(&mut self, entry: TtEntry) {
+        let slot = &mut self.tt[(entry.hash as usize) % TT_SIZE];
+        if slot.is_none_or(|old| old.depth <= entry.depth || old.hash != entry.hash) {
+            *slot = Some(entry);
+        }
+    }
+
+    fn ordered_moves(&self, board: &Board, tt_move: Option<ChessMove>, ply: usize) -> Vec<ChessMove> {
+        let killers = self.killers.get(ply).copied().unwrap_or([None; 2]);
+        let mut moves: Vec<(i32, ChessMove)> = MoveGen::new_legal(board)
+            .map(|m| {
+                let order = if Some(m) == tt_move {
+                    1_000_000
+                } else if is_capture(board, m) {
+                    100_000 + capture_order(board, m)
+                } else if m.get_promotion() == Some(Piece::Queen) {
+                    90_000
+                } else if killers.contains(&Some(m)) {
+                    80_000
+                } else {
+                    0
+                };
+                (order, m)
+            })
+            .collect();
+        moves.sort_by_key(|&(order, _)| Reverse(order));
+        moves.into_iter().map(|(_, m)| m).collect()
+    }
+
+    fn tick(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes >= self.max_nodes {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    fn is_draw(&self, board: &Board, halfmove_clock: u32) -> bool {
+        if halfmove_clock >= 100 || crate::has_insufficient_material(board) {
+            return true;
+        }
+        // Inside the search a single repetition is scored as a draw.
+        let hash = board.get_hash();
+        self.path.iter().rev().skip(1).any(|&h| h == hash)
+    }
+
+    fn quiesce(&mut self, board: &Board, mut alpha: i32, beta: i32) -> i32 {
+        if self.tick() {
+            return 0;
+        }
+
+        let stand_pat = side_eval(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        // Captures, en passant included, and promotions to a queen.
+        let mut targets = *board.color_combined(!board.side_to_move());
+        if let Some(square) = board.en_passant().and_then(|pawn| pawn.forward(board.side_to_move())) {
+            targets |= BitBoard::from_square(square);
+        }
+        targets |= get_rank(if board.side_to_move() == Color::White { Rank::Eighth } else { Rank::First });
+        let mut captures = MoveGen::new_legal(board);
+        captures.set_iterator_mask(targets);
+        let mut captures: Vec<ChessMove> = captures
+            .filter(|m| is_capture(board, *m) || m.get_promotion() == Some(Piece::Queen))
+            .collect();
+        captures.sort_by_key(|m| -capture_order(board, *m));
+
+        for chess_move in captures {
+            let score = -self.quiesce(&board.make_move_new(chess_move), -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+
+    fn negamax(&mut self, board: &Board, depth: u32, ply: usize, mut alpha: i32, beta: i32, halfmove_clock: u32) -> i32 {
+        if self.tick() {
+            return 0;
+        }
+        if ply > 0 && self.is_draw(board, halfmove_clock) {
+            return 0;
+        }
+
+        let in_check = *board.checkers() != EMPTY;
+        let depth = if in_check && ply < MAX_DEPTH as usize { depth + 1 } else { depth };
+        if depth == 0 {
+            return self.quiesce(board, alpha, beta);
+        }
+
+        let hash = board.get_hash();
+        let tt_entry = self.probe(hash);
+        if let Some(entry) = tt_entry {
+            if ply > 0 && entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        let moves = self.ordered_moves(board, tt_entry.and_then(|e| e.best_move), ply);
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+
+        for chess_move in moves {
+            let next = board.make_move_new(chess_move);
+            let clock = if is_irreversible(board, chess_move) { 0 } else { halfmove_clock + 1 };
+
+            self.path.push(next.get_hash());
+            let score = -self.negamax(&next, depth - 1, ply + 1, -beta, -alpha, clock);
+            self.path.pop();
+
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(chess_move);
+            }
+            if score > alpha {
+                alpha = score;
+            }
+            if alpha >= beta {
+                if !is_capture(board, chess_move) {
+                    if let Some(killers) = self.killers.get_mut(ply) {
+                        if killers[0] != Some(chess_move) {
+                            killers[1] = killers[0];
+                            killers[0] = Some(chess_move);
+                        }
+                    }
+                }
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.store(TtEntry { hash, depth, score: score_to_tt(best_score, ply), bound, best_move });
+
+        best_score
+    }
+
+    /// Scores every root move, with a full window for the moves `exact`
+    /// covers and a narrowing one for the rest.
+    fn search_root(&mut self, board: &Board, depth: u32, halfmove_clock: u32, exact: Exact, order: &[ChessMove]) -> Option<Vec<(ChessMove, i32)>> {
+        let mut alpha = -INFINITY;
+        let mut scores = Vec::with_capacity(order.len());
+
+        for &chess_move in order {
+            let next = board.make_move_new(chess_move);
+            let clock = if is_irreversible(board, chess_move) { 0 } else { halfmove_clock + 1 };
+            let window_alpha = if exact.covers(chess_move) { -INFINITY } else { alpha };
+
+            self.path.push(next.get_hash());
+            let score = -self.negamax(&next, depth - 1, 1, -INFINITY, -window_alpha, clock);
+            self.path.pop();
+
+            if self.aborted {
+                return None;
+            }
+            alpha = alpha.max(score);
+            scores.push((chess_move, score));
+        }
+
+        Some(scores)
+    }
+
+    /// Iterative deepening up to `depth_limit`. Returns the root move scores
+    /// of the deepest completed iteration, best first, and that depth, or
+    /// `None` if the node budget ran out during the first one.
+    fn deepen(&mut self, board: &Board, depth_limit: u32, halfmove_clock: u32, exact: Exact) -> Option<(Vec<(ChessMove, i32)>, u32)> {
+        let mut order = self.ordered_moves(board, None, 0);
+        let mut completed = None;
+
+        for depth in 1..=depth_limit {
+            match self.search_root(board, depth, halfmove_clock, exact, &order) {
+                Some(mut scores) => {
+                    scores.sort_by_key(|&(_, score)| Reverse(score));
+                    order = scores.iter().map(|(m, _)| *m).collect();
+                    let mate_found = scores[0].1.abs() >= MATE_THRESHOLD;
+                    completed = Some((scores, depth));
+                    if mate_found {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        completed
+    }
+}
+
+/// The deepest search a skill level is allowed, so low levels also stay
+/// shallow when given a long time budget.
+fn skill_depth(skill: u32) -> u32 {
+    if skill >= MAX_SKILL { MAX_DEPTH } else { 1 + skill / 3 }
+}
+
+/// How many centipawns of random error each root move is given.
+fn skill_noise(skill: u32) -> i32 {
+    (MAX_SKILL - skill.min(MAX_SKILL)) as i32 * 40
+}
+
+/// Searches `board` and picks a move for the side to move. `history` holds
+/// the hashes of earlier positions since the last capture or pawn move, so
+/// the engine can steer towards or away from repetitions. Returns `None`
+/// when there is no legal move.
+pub fn search(board: &Board, history: &[u64], halfmove_clock: u32, limits: Limits, skill: u32) -> Option<SearchResult> {
+    if board.status() != BoardStatus::Ongoing {
+        return None;
+    }
+
+    let skill = skill.min(MAX_SKILL);
+    let depth_limit = limits.depth.clamp(1, MAX_DEPTH).min(skill_depth(skill));
+    // Weaker levels need exact scores for all moves to pick from.
+    let exact = if skill < MAX_SKILL { Exact::All } else { Exact::Best };
+
+    let mut searcher = Searcher::new(history, limits.max_nodes);
+    let (scores, depth) = match searcher.deepen(board, depth_limit, halfmove_clock, exact) {
+        Some(completed) => completed,
+        None => {
+            let first = searcher.ordered_moves(board, None, 0)[0];
+            let score = side_eval(&board.make_move_new(first));
+            (vec![(first, -score)], 0)
+        }
+    };
+
+    let mut rng = XorShift(board.get_hash() ^ (skill as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+    let amplitude = skill_noise(skill);
+    // Ties go to the move searched first: with a narrowing window, later
+    // moves that tie are only bounds.
+    let mut best: Option<(ChessMove, i32, i32)> = None;
+    for &(chess_move, score) in &scores {
+        let noisy = score + rng.noise(amplitude);
+        if best.is_none_or(|(_, _, best_noisy)| noisy > best_noisy) {
+            best = Some((chess_move, score, noisy));
+        }
+    }
+    let (best_move, score, _) = best?;
+
+    Some(SearchResult {
+        best_move,
+        score,
+        depth,
+        nodes: searcher.nodes,
+    })
+}
+
+/// How a played move compares with the engine's choice in the same
+/// position. Scores are centipawns from the mover's point of view.
+pub struct MoveGrade {
+    pub best_move: ChessMove,
+    pub best_score: i32,
+    pub score: i32,
+    pub depth: u32,
+}
+
+/// Searches `board` at full strength and scores `played` against the best
+/// move found. The played move gets a full window in every iteration, so
+/// both scores are exact and come from the same completed depth, however
+/// the node budget runs out.
+pub fn grade_move(board: &Board, history: &[u64], halfmove_clock: u32, limits: Limits, played: ChessMove) -> Option<MoveGrade> {
+    if board.status() != BoardStatus::Ongoing || !board.legal(played) {
+        return None;
+    }
+
+    let mut searcher = Searcher::new(history, limits.max_nodes);
+    let depth_limit = limits.depth.clamp(1, MAX_DEPTH);
+    let (scores, depth) = match searcher.deepen(board, depth_limit, halfmove_clock, Exact::With(played)) {
+        Some(completed) => completed,
+        None => {
+            let mut scores: Vec<(ChessMove, i32)> = MoveGen::new_legal(board)
+                .map(|chess_move| (chess_move, -side_eval(&board.make_move_new(chess_move))))
+                .collect();
+            scores.sort_by_key(|&(_, score)| Reverse(score));
+            (scores, 0)
+        }
+    };
+
+    let (best_move, best_score) = scores[0];
+    let score = scores.iter().find(|(chess_move, _)| *chess_move == played).map_or(best_score, |&(_, score)| score);
+
+    Some(MoveGrade {
+        best_move,
+        best_score,
+        score: score.min(best_score),
+        depth,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn board(fen: &str) -> Board {
+        Board::from_str(fen).unwrap()
+    }
+
+    #[test]
+    fn limits_keep_depth_and_budget_apart() {
+        let limits = Limits::new(32, 0);
+        assert_eq!((limits.depth, limits.max_nodes), (32, DEFAULT_NODES));
+
+        let limits = Limits::new(0, 1_000);
+        assert_eq!((limits.depth, limits.max_nodes), (MAX_DEPTH, 1_000));
+
+        let limits = Limits::new(100, u64::MAX);
+        assert_eq!((limits.depth, limits.max_nodes), (MAX_DEPTH, MAX_NODES));
+    }
+
+    #[test]
+    fn depth_or_millis_is_a_depth_up_to_the_max_and_a_budget_above() {
+        let limits = Limits::from_depth_or_millis(6);
+        assert_eq!((limits.depth, limits.max_nodes), (6, MAX_NODES));
+
+        let limits = Limits::from_depth_or_millis(0);
+        assert_eq!((limits.depth, limits.max_nodes), (MAX_DEPTH, DEFAULT_NODES));
+
+        let limits = Limits::from_depth_or_millis(1_000);
+        assert_eq!((limits.depth, limits.max_nodes), (MAX_DEPTH, 1_000 * NODES_PER_MILLI));
+
+        let limits = Limits::from_depth_or_millis(u32::MAX);
+        assert_eq!((limits.depth, limits.max_nodes), (MAX_DEPTH, MAX_NODES));
+    }
+
+    #[test]
+    fn a_deep_search_stops_at_the_budget() {
+        let result = search(&Board::default(), &[], 0, Limits::new(32, 20_000), MAX_SKILL).unwrap();
+        assert!(result.nodes <= 20_000, "{} nodes", result.nodes);
+        assert!(result.depth < 32);
+        assert!(Board::default().legal(result.best_move));
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        // Scholar's mate: Qxf7#.
+        let board = board("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4");
+        let result = search(&board, &[], 4, Limits::new(4, 0), MAX_SKILL).unwrap();
+        assert_eq!(result.best_move.to_string(), "h5f7");
+        assert_eq!(result.mate_in(), Some(1));
+    }
+
+    #[test]
+    fn quiescence_sees_promotions() {
+        // Nxd5 wins the queen but lets Black play a1=Q; h8=Q covers a1.
+        let board = board("8/5k1P/8/3q4/8/4N3/p6K/8 w - - 0 1");
+        let result = search(&board, &[], 0, Limits::new(1, 0), MAX_SKILL).unwrap();
+        assert_eq!(result.best_move.to_string(), "h7h8q");
+    }
+
+    #[test]
+    fn quiescence_sees_en_passant() {
+        // dxe3 e.p. is the only capture.
+        let board = board("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1");
+        let mut searcher = Searcher::new(&[], MAX_NODES);
+        let after = board.make_move_new(ChessMove::from_str("d4e3").unwrap());
+        assert_eq!(searcher.quiesce(&board, -INFINITY, INFINITY), -side_eval(&after));
+    }
+
+    #[test]
+    fn the_same_request_gives_the_same_move() {
+        let board = board("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        for skill in [0, 10, MAX_SKILL] {
+            let first = search(&board, &[], 2, Limits::new(3, 0), skill).unwrap();
+            let second = search(&board, &[], 2, Limits::new(3, 0), skill).unwrap();
+            assert_eq!(first.best_move, second.best_move);
+        }
+    }
+
+    #[test]
+    fn no_move_once_the_game_is_over() {
+        let mated = board("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+        assert!(search(&mated, &[], 1, Limits::new(2, 0), MAX_SKILL).is_none());
+    }
+}
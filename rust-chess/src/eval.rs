@@ -0,0 +1,407 @@
+use chess::{
+    get_bishop_moves, get_file, get_king_moves, get_knight_moves, get_rank, get_rook_moves, BitBoard, Board, Color, File,
+    Piece, Rank, Square, ALL_PIECES, EMPTY,
+};
+
+pub const PAWN_VALUE: i32 = 100;
+
+pub fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => PAWN_VALUE,
+        Piece::Knight => 320,
+        Piece::Bishop => 330,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 0,
+    }
+}
+
+// Piece-square tables from White's point of view, rank 8 first so they read
+// like a board diagram. Black looks them up mirrored.
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+/// Total non-pawn material at the start of the game, used to blend the
+/// middlegame and endgame king tables.
+const OPENING_PHASE: i32 = 2 * (2 * 320 + 2 * 330 + 2 * 500 + 900);
+
+/// Non-pawn material left on the board, from 0 (pawn endgame) up to
+/// `OPENING_PHASE`.
+fn phase(board: &Board) -> i32 {
+    let phase = [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+        .iter()
+        .map(|&piece| board.pieces(piece).popcnt() as i32 * piece_value(piece))
+        .sum();
+    OPENING_PHASE.min(phase)
+}
+
+fn table_index(square: Square, color: Color) -> usize {
+    let rank = square.get_rank().to_index();
+    let file = square.get_file().to_index();
+    match color {
+        Color::White => (7 - rank) * 8 + file,
+        Color::Black => rank * 8 + file,
+    }
+}
+
+fn square_bonus(piece: Piece, index: usize, phase: i32) -> i32 {
+    match piece {
+        Piece::Pawn => PAWN_TABLE[index],
+        Piece::Knight => KNIGHT_TABLE[index],
+        Piece::Bishop => BISHOP_TABLE[index],
+        Piece::Rook => ROOK_TABLE[index],
+        Piece::Queen => QUEEN_TABLE[index],
+        Piece::King => {
+            (KING_MIDDLEGAME_TABLE[index] * phase + KING_ENDGAME_TABLE[index] * (OPENING_PHASE - phase))
+                / OPENING_PHASE
+        }
+    }
+}
+
+/// Centipawn terms of the static evaluation, each positive when White is
+/// better. `total()` is what the engine searches with.
+#[derive(Default, Clone, Copy)]
+pub struct Evaluation {
+    pub material: i32,
+    pub placement: i32,
+    pub mobility: i32,
+    pub king_safety: i32,
+    pub pawn_structure: i32,
+}
+
+impl Evaluation {
+    pub fn total(&self) -> i32 {
+        self.material + self.placement + self.mobility + self.king_safety + self.pawn_structure
+    }
+}
+
+fn sign(color: Color) -> i32 {
+    if color == Color::White { 1 } else { -1 }
+}
+
+/// Centipawns per reachable square beyond a typical count, per piece type.
+fn mobility_weight(piece: Piece) -> (i32, i32) {
+    match piece {
+        Piece::Knight => (4, 4),
+        Piece::Bishop => (5, 7),
+        Piece::Rook => (2, 7),
+        Piece::Queen => (1, 14),
+        _ => (0, 0),
+    }
+}
+
+fn mobility(board: &Board, color: Color) -> i32 {
+    let blockers = *board.combined();
+    let own = *board.color_combined(color);
+    let mut score = 0;
+
+    for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        let (weight, typical) = mobility_weight(piece);
+        for square in board.pieces(piece) & own {
+            let targets = match piece {
+                Piece::Knight => get_knight_moves(square),
+                Piece::Bishop => get_bishop_moves(square, blockers),
+                Piece::Rook => get_rook_moves(square, blockers),
+                _ => get_bishop_moves(square, blockers) | get_rook_moves(square, blockers),
+            };
+            score += weight * ((targets & !own).popcnt() as i32 - typical);
+        }
+    }
+
+    score
+}
+
+fn file_mask(file: usize) -> BitBoard {
+    get_file(File::from_index(file))
+}
+
+fn adjacent_files(file: usize) -> BitBoard {
+    let mut mask = EMPTY;
+    if file > 0 {
+        mask |= file_mask(file - 1);
+    }
+    if file < 7 {
+        mask |= file_mask(file + 1);
+    }
+    mask
+}
+
+/// Squares strictly in front of `square` from `color`'s point of view.
+fn ranks_ahead(square: Square, color: Color) -> BitBoard {
+    let rank = square.get_rank().to_index();
+    let bits = match color {
+        Color::White if rank < 7 => !0u64 << ((rank + 1) * 8),
+        Color::Black if rank > 0 => !0u64 >> ((8 - rank) * 8),
+        _ => 0,
+    };
+    BitBoard::new(bits)
+}
+
+/// Pawn shield in front of the king and enemy pressure around it, faded
+/// out as pieces come off the board.
+fn king_safety(board: &Board, color: Color, phase: i32) -> i32 {
+    let king = board.king_square(color);
+    let file = king.get_file().to_index();
+    let own_pawns = board.pieces(Piece::Pawn) & board.color_combined(color);
+    let zone = get_king_moves(king);
+
+    // Own pawns on the king's file and its neighbours, one or two ranks up.
+    let shield_files = file_mask(file) | adjacent_files(file);
+    let rank = king.get_rank().to_index() as i32;
+    let step = if color == Color::White { 1 } else { -1 };
+    let mut near_ranks = EMPTY;
+    for offset in 1..=2 {
+        let ahead = rank + step * offset;
+        if (0..8).contains(&ahead) {
+            near_ranks |= get_rank(Rank::from_index(ahead as usize));
+        }
+    }
+    let shield = (own_pawns & shield_files & near_ranks).popcnt() as i32;
+    let open_files = (0..8)
+        .filter(|&f| (shield_files & file_mask(f)) != EMPTY)
+        .filter(|&f| (own_pawns & file_mask(f)) == EMPTY)
+        .count() as i32;
+
+    let blockers = *board.combined();
+    let enemy = *board.color_combined(!color);
+    let mut attackers = 0;
+    for square in enemy & !board.pieces(Piece::Pawn) & !board.pieces(Piece::King) {
+        let attacks = match board.piece_on(square) {
+            Some(Piece::Knight) => get_knight_moves(square),
+            Some(Piece::Bishop) => get_bishop_moves(square, blockers),
+            Some(Piece::Rook) => get_rook_moves(square, blockers),
+            Some(Piece::Queen) => get_bishop_moves(square, blockers) | get_rook_moves(square, blockers),
+            _ => EMPTY,
+        };
+        attackers += (attacks & zone).popcnt() as i32;
+    }
+
+    let middlegame = 12 * shield.min(3) - 20 * open_files - 8 * attackers;
+    middlegame * phase / OPENING_PHASE
+}
+
+/// Doubled and isolated pawns cost, passed pawns gain more the further
+/// they have advanced and the emptier the board is.
+fn pawn_structure(board: &Board, color: Color, phase: i32) -> i32 {
+    let own_pawns = board.pieces(Piece::Pawn) & board.color_combined(color);
+    let enemy_pawns = board.pieces(Piece::Pawn) & board.color_combined(!color);
+    let mut score = 0;
+
+    for file in 0..8 {
+        let on_file = (own_pawns & file_mask(file)).popcnt() as i32;
+        if on_file > 1 {
+            score -= 15 * (on_file - 1);
+        }
+        if on_file > 0 && (own_pawns & adjacent_files(file)) == EMPTY {
+            score -= 15 * on_file;
+        }
+    }
+
+    for square in own_pawns {
+        let file = square.get_file().to_index();
+        let front_span = ranks_ahead(square, color) & (file_mask(file) | adjacent_files(file));
+        if (enemy_pawns & front_span) == EMPTY {
+            let advance = match color {
+                Color::White => square.get_rank().to_index() as i32,
+                Color::Black => 7 - square.get_rank().to_index() as i32,
+            };
+            let bonus = advance * advance * 3;
+            score += bonus + bonus * (OPENING_PHASE - phase) / OPENING_PHASE;
+        }
+    }
+
+    score
+}
+
+/// Every term of the evaluation, in centipawns from White's point of view.
+pub fn evaluate_terms(board: &Board) -> Evaluation {
+    let phase = phase(board);
+    let mut terms = Evaluation::default();
+
+    for piece in ALL_PIECES {
+        for color in [Color::White, Color::Black] {
+            for square in board.pieces(piece) & board.color_combined(color) {
+                terms.material += sign(color) * piece_value(piece);
+                terms.placement += sign(color) * square_bonus(piece, table_index(square, color), phase);
+            }
+        }
+    }
+
+    for color in [Color::White, Color::Black] {
+        terms.mobility += sign(color) * mobility(board, color);
+        terms.king_safety += sign(color) * king_safety(board, color, phase);
+        terms.pawn_structure += sign(color) * pawn_structure(board, color, phase);
+    }
+
+    terms
+}
+
+/// The static evaluation in centipawns, positive when White is better.
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_terms(board).total()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn board(fen: &str) -> Board {
+        Board::from_str(fen).unwrap()
+    }
+
+    /// The same position with the colours swapped and the board flipped.
+    fn mirrored(fen: &str) -> Board {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let swap_case = |s: &str| -> String {
+            s.chars().map(|c| if c.is_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() }).collect()
+        };
+        let placement: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+        let side = if fields[1] == "w" { "b" } else { "w" };
+        board(&format!("{} {} {} - 0 1", placement.join("/"), side, swap_case(fields[2])))
+    }
+
+    const ITALIAN: &str = "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+
+    #[test]
+    fn the_start_position_is_level_on_every_term() {
+        let terms = evaluate_terms(&Board::default());
+        let all = [terms.material, terms.placement, terms.mobility, terms.king_safety, terms.pawn_structure];
+        assert_eq!(all, [0; 5]);
+    }
+
+    #[test]
+    fn swapping_colours_negates_every_term() {
+        for fen in [ITALIAN, "4k3/8/8/3p4/8/8/PP3P2/4K3 w - - 0 1", "r3k2r/1p3ppp/8/8/8/8/PPP5/2KR4 b kq - 0 1"] {
+            let white = evaluate_terms(&board(fen));
+            let black = evaluate_terms(&mirrored(fen));
+            assert_eq!(white.material, -black.material, "{}", fen);
+            assert_eq!(white.placement, -black.placement, "{}", fen);
+            assert_eq!(white.mobility, -black.mobility, "{}", fen);
+            assert_eq!(white.king_safety, -black.king_safety, "{}", fen);
+            assert_eq!(white.pawn_structure, -black.pawn_structure, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn the_score_is_the_sum_of_the_terms() {
+        let terms = evaluate_terms(&board(ITALIAN));
+        let sum = terms.material + terms.placement + terms.mobility + terms.king_safety + terms.pawn_structure;
+        assert_eq!(evaluate(&board(ITALIAN)), sum);
+        assert_eq!(evaluate_terms(&board("4k3/8/8/8/8/8/8/3QK3 w - - 0 1")).material, 900);
+    }
+
+    #[test]
+    fn centralised_pieces_are_more_mobile() {
+        let central = board("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let cornered = board("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+        assert!(mobility(&central, Color::White) > mobility(&cornered, Color::White));
+    }
+
+    #[test]
+    fn an_open_king_is_less_safe_and_only_matters_with_pieces_on() {
+        let shielded = Board::default();
+        let opened = board("rnbqkbnr/pppppppp/8/8/8/8/PPP2PPP/RNBQKBNR w KQkq - 0 1");
+        let opening = phase(&shielded);
+        assert!(king_safety(&shielded, Color::White, opening) > king_safety(&opened, Color::White, opening));
+
+        let bare = board("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(king_safety(&bare, Color::White, phase(&bare)), 0);
+    }
+
+    #[test]
+    fn pawn_weaknesses_cost_and_passed_pawns_gain_as_they_advance() {
+        let connected = board("4k3/8/8/8/8/8/PP6/4K3 w - - 0 1");
+        let doubled = board("4k3/8/8/8/8/P7/P7/4K3 w - - 0 1");
+        assert!(pawn_structure(&connected, Color::White, 0) > pawn_structure(&doubled, Color::White, 0));
+
+        let far = board("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        let near = board("4k3/8/8/8/8/8/P7/4K3 w - - 0 1");
+        assert!(pawn_structure(&far, Color::White, 0) > pawn_structure(&near, Color::White, 0));
+        assert!(pawn_structure(&far, Color::White, 0) > pawn_structure(&far, Color::White, OPENING_PHASE));
+
+        let blocked = board("4k3/p7/8/8/8/8/P7/4K3 w - - 0 1");
+        assert!(pawn_structure(&near, Color::White, 0) > pawn_structure(&blocked, Color::White, 0));
+    }
+}
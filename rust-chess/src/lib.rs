@@ -1,362 +1,793 @@
-use chess::{Board, ChessMove, Color, Game, MoveGen, Piece, Square};
+use chess::{Board, BoardBuilder, ChessMove, Color, MoveGen, Piece, Square, ALL_SQUARES};
 use serde::{Deserialize, Serialize};
-use std::ffi::{CStr, CString};
+use arena_game_sdk::ffi::{from_c_string, read_state, to_c_result, to_c_string, write_state};
+use arena_game_sdk::guard::guard;
+use arena_game_sdk::host_log;
+use arena_game_sdk::log::Level;
+use arena_game_sdk::{Capabilities, GameError, Outcome};
 use std::os::raw::c_char;
+use std::cell::OnceCell;
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize)]
+mod analysis;
+mod engine;
+mod eval;
+mod pgn;
+mod rejection;
+mod san;
+#[cfg(test)]
+mod tests;
+
+use rejection::MoveError;
+
+#[derive(Serialize, Deserialize, Clone)]
 struct GameState {
     fen: String,
     moves: Vec<String>,
     current_player: String,
     move_count: u32,
+    #[serde(default)]
+    halfmove_clock: u32,
+    #[serde(default = "default_fullmove_number")]
+    fullmove_number: u32,
+    /// Set when the game was imported from a FEN or PGN that does not start
+    /// from the standard position; `moves` are replayed from here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_fen: Option<String>,
+    /// The replayed position, built on first use. A state kept in the
+    /// handle registry reuses it across calls instead of replaying `moves`
+    /// each time; `play` advances it along with the other fields.
+    #[serde(skip)]
+    position: OnceCell<Position>,
 }
 
-static mut GAME: Option<Game> = None;
-
-unsafe fn get_game() -> &'static mut Game {
-    if GAME.is_none() {
-        GAME = Some(Game::new());
+impl GameState {
+    fn position(&self) -> &Position {
+        self.position.get_or_init(|| Position::from_state(self))
     }
-    GAME.as_mut().unwrap()
 }
 
-fn to_c_string(s: String) -> *mut c_char {
-    CString::new(s).unwrap().into_raw()
+fn default_fullmove_number() -> u32 {
+    1
 }
 
-fn from_c_string(ptr: *const c_char) -> String {
-    unsafe {
-        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+/// Parses a FEN into a legal position. `Board` assumes each side has exactly
+/// one king and reads out of bounds when a king is missing, so the kings are
+/// counted on the builder before converting it.
+fn parse_fen(fen: &str) -> Option<Board> {
+    let builder = BoardBuilder::from_str(fen).ok()?;
+    for color in [Color::White, Color::Black] {
+        let kings = ALL_SQUARES.iter().filter(|&&square| builder[square] == Some((Piece::King, color))).count();
+        if kings != 1 {
+            return None;
+        }
     }
+    Board::try_from(&builder).ok()
 }
 
-#[no_mangle]
-pub extern "C" fn get_initial_state() -> *mut c_char {
-    let board = Board::default();
-    let state = GameState {
-        fen: board.to_string(),
-        moves: Vec::new(),
-        current_player: "player1".to_string(),
-        move_count: 0,
+/// Reads the halfmove clock and fullmove number from a FEN, falling back to
+/// "0 1" when they are missing.
+fn fen_counters(fen: &str) -> (u32, u32) {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let halfmove_clock = fields.get(4).and_then(|f| f.parse().ok()).unwrap_or(0);
+    let fullmove_number = fields.get(5).and_then(|f| f.parse().ok()).unwrap_or(1);
+    (halfmove_clock, fullmove_number.max(1))
+}
+
+/// `Board::to_string()` always ends in "0 1" and gives the square of the
+/// pawn that can be taken en passant; swap in the real counters and the
+/// square behind the pawn, which is what FEN records.
+fn to_fen(board: &Board, halfmove_clock: u32, fullmove_number: u32) -> String {
+    let board_fen = board.to_string();
+    let fields: Vec<&str> = board_fen.split(' ').take(3).collect();
+    let en_passant = match board.en_passant() {
+        // The pawn belongs to the side that just moved.
+        Some(square) if board.side_to_move() == Color::White => square.up().map(|s| s.to_string()),
+        Some(square) => square.down().map(|s| s.to_string()),
+        None => None,
     };
-    
-    unsafe {
-        *get_game() = Game::new();
+    format!(
+        "{} {} {} {}",
+        fields.join(" "),
+        en_passant.as_deref().unwrap_or("-"),
+        halfmove_clock,
+        fullmove_number
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DrawReason {
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+}
+
+impl DrawReason {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::ThreefoldRepetition => "threefold_repetition",
+            DrawReason::FiftyMoveRule => "fifty_move_rule",
+            DrawReason::InsufficientMaterial => "insufficient_material",
+        }
     }
-    
-    to_c_string(serde_json::to_string(&state).unwrap())
 }
 
-#[no_mangle]
-pub extern "C" fn get_valid_moves(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
+/// The current board together with the history needed for the draw rules,
+/// rebuilt by replaying `GameState.moves` from the starting position.
+#[derive(Clone)]
+struct Position {
+    board: Board,
+    history: Vec<u64>,
+    halfmove_clock: u32,
+    start: Board,
+    start_fen: String,
+    start_fullmove: u32,
+    played: Vec<ChessMove>,
+    /// False when `moves` did not lead to the stored FEN and the position
+    /// starts from the FEN instead.
+    replayed: bool,
+}
+
+impl Position {
+    fn from_state(state: &GameState) -> Position {
+        let fen_board = Board::from_str(&state.fen).unwrap_or_default();
+
+        let start_fen = state.start_fen.clone().unwrap_or_else(|| to_fen(&Board::default(), 0, 1));
+        let start = Board::from_str(&start_fen).unwrap_or_default();
+        let mut board = start;
+        let mut history = vec![board.get_hash()];
+        let mut played = Vec::new();
+
+        for move_str in &state.moves {
+            let chess_move = match ChessMove::from_str(move_str) {
+                Ok(chess_move) if board.legal(chess_move) => chess_move,
+                _ => break,
+            };
+
+            // Positions before a capture or pawn move can never recur.
+            if resets_halfmove_clock(&board, chess_move) {
+                history.clear();
+            }
+
+            board = board.make_move_new(chess_move);
+            history.push(board.get_hash());
+            played.push(chess_move);
         }
-    });
 
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    let mut moves = Vec::new();
+        // The move list does not lead to the stored FEN, so the history is
+        // unknown and only the position itself can be judged.
+        if board != fen_board {
+            return Position {
+                board: fen_board,
+                history: vec![fen_board.get_hash()],
+                halfmove_clock: state.halfmove_clock,
+                start: fen_board,
+                start_fen: state.fen.clone(),
+                start_fullmove: state.fullmove_number,
+                played: Vec::new(),
+                replayed: false,
+            };
+        }
 
-    for chess_move in MoveGen::new_legal(&board) {
-        moves.push(chess_move.to_string());
+        Position {
+            board: fen_board,
+            history,
+            halfmove_clock: state.halfmove_clock,
+            start,
+            start_fullmove: fen_counters(&start_fen).1,
+            start_fen,
+            played,
+            replayed: true,
+        }
     }
-    if moves.len() < 30 && !moves.contains(&"e2e4".to_string()) {
-        eprintln!("🐛 WASM Chess Debug:");
-        eprintln!("  FEN: {}", state.fen);
-        eprintln!("  Move count: {}", state.move_count);
-        eprintln!("  Moves generated: {}", moves.len());
-        eprintln!("  Contains e2e4: {}", moves.contains(&"e2e4".to_string()));
-        eprintln!("  First 10 moves: {:?}", &moves[..moves.len().min(10)]);
+
+    fn repetitions(&self) -> usize {
+        let hash = self.board.get_hash();
+        self.history.iter().filter(|&&h| h == hash).count()
     }
 
-    to_c_string(serde_json::to_string(&moves).unwrap())
-}
+    fn draw_reason(&self) -> Option<DrawReason> {
+        match self.board.status() {
+            chess::BoardStatus::Checkmate => None,
+            chess::BoardStatus::Stalemate => Some(DrawReason::Stalemate),
+            chess::BoardStatus::Ongoing => {
+                if has_insufficient_material(&self.board) {
+                    Some(DrawReason::InsufficientMaterial)
+                } else if self.halfmove_clock >= 100 {
+                    Some(DrawReason::FiftyMoveRule)
+                } else if self.repetitions() >= 3 {
+                    Some(DrawReason::ThreefoldRepetition)
+                } else {
+                    None
+                }
+            }
+        }
+    }
 
-#[no_mangle]
-pub extern "C" fn apply_move(state_ptr: *const c_char, move_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let move_str = from_c_string(move_ptr);
-    
-    let mut state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
+    fn is_game_over(&self) -> bool {
+        self.board.status() == chess::BoardStatus::Checkmate || self.draw_reason().is_some()
+    }
+
+    /// The PGN result token: "1-0", "0-1", "1/2-1/2" or "*" while ongoing.
+    fn result(&self) -> &'static str {
+        if self.board.status() == chess::BoardStatus::Checkmate {
+            if self.board.side_to_move() == Color::White { "0-1" } else { "1-0" }
+        } else if self.draw_reason().is_some() {
+            "1/2-1/2"
+        } else {
+            "*"
         }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    
-    if let Ok(chess_move) = ChessMove::from_str(&move_str) {
-        if board.legal(chess_move) {
-            let new_board = board.make_move_new(chess_move);
-            state.fen = new_board.to_string();
-            state.moves.push(move_str);
-            state.move_count += 1;
-            state.current_player = if state.current_player == "player1" { 
-                "player2".to_string() 
-            } else { 
-                "player1".to_string() 
+    }
+
+    fn to_pgn(&self, headers: &serde_json::Map<String, serde_json::Value>) -> String {
+        let mut tags: Vec<(String, String)> = vec![
+            ("Event".to_string(), "LLM Arena Match".to_string()),
+            ("Site".to_string(), "LLM Arena".to_string()),
+            ("Date".to_string(), "????.??.??".to_string()),
+            ("Round".to_string(), "-".to_string()),
+            ("White".to_string(), "player1".to_string()),
+            ("Black".to_string(), "player2".to_string()),
+        ];
+        for (name, value) in headers {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                other => other.to_string(),
             };
+            match tags.iter_mut().find(|(tag, _)| tag == name) {
+                Some(tag) => tag.1 = value,
+                None => tags.push((name.clone(), value)),
+            }
         }
+
+        if self.start != Board::default() || self.start_fullmove != 1 {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), self.start_fen.clone()));
+        }
+
+        let result = self.result();
+        tags.retain(|(tag, _)| tag != "Result");
+        tags.push(("Result".to_string(), result.to_string()));
+        if !tags.iter().any(|(tag, _)| tag == "Termination") {
+            let termination = if result == "*" { "unterminated" } else { "normal" };
+            tags.push(("Termination".to_string(), termination.to_string()));
+        }
+
+        let comment = match self.draw_reason() {
+            Some(reason) => Some(format!("Draw by {}", reason.as_str().replace('_', " "))),
+            None if result != "*" => Some("Checkmate".to_string()),
+            None => None,
+        };
+
+        pgn::PgnGame {
+            tags,
+            start: self.start,
+            start_fullmove: self.start_fullmove,
+            moves: &self.played,
+            result,
+            comment,
+        }
+        .write()
     }
-    
-    to_c_string(serde_json::to_string(&state).unwrap())
 }
 
-#[no_mangle]
-pub extern "C" fn is_game_over(state_ptr: *const c_char) -> i32 {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    
-    if board.status() != chess::BoardStatus::Ongoing {
-        1
-    } else {
-        0
+/// Accepts UCI ("g1f3", "e7e8q") or SAN ("Nf3", "e8=Q+").
+fn parse_move(board: &Board, move_str: &str) -> Result<ChessMove, MoveError> {
+    match ChessMove::from_str(move_str.trim()) {
+        Ok(chess_move) if board.legal(chess_move) => Ok(chess_move),
+        _ => san::from_san(board, move_str).ok_or_else(|| rejection::explain(board, move_str)),
     }
 }
 
-#[no_mangle]
-pub extern "C" fn get_winner(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    
-    let winner = match board.status() {
-        chess::BoardStatus::Checkmate => {
-            if board.side_to_move() == Color::White {
-                "player2"
-            } else {
-                "player1"
+/// Plays a legal move on `board` (the position in `state.fen`) and updates
+/// every field of the state to match.
+fn play(state: &mut GameState, board: &Board, chess_move: ChessMove) {
+    let new_board = board.make_move_new(chess_move);
+    if resets_halfmove_clock(board, chess_move) {
+        state.halfmove_clock = 0;
+    } else {
+        state.halfmove_clock = state.halfmove_clock.saturating_add(1);
+    }
+    if board.side_to_move() == Color::Black {
+        state.fullmove_number = state.fullmove_number.saturating_add(1);
+    }
+    state.fen = to_fen(&new_board, state.halfmove_clock, state.fullmove_number);
+    state.moves.push(chess_move.to_string());
+    // Keep a cached position in step rather than replaying every move on
+    // the next call. One that could not replay the moves is dropped instead,
+    // so it is rebuilt from the new state just as a copy of it would be.
+    match state.position.get_mut() {
+        Some(position) if position.replayed => {
+            if resets_halfmove_clock(board, chess_move) {
+                position.history.clear();
             }
-        },
-        chess::BoardStatus::Stalemate => "draw",
-        _ => ""
+            position.board = new_board;
+            position.history.push(new_board.get_hash());
+            position.halfmove_clock = state.halfmove_clock;
+            position.played.push(chess_move);
+        }
+        _ => {
+            state.position.take();
+        }
+    }
+    state.move_count = state.move_count.saturating_add(1);
+    state.current_player = if state.current_player == "player1" { 
+        "player2".to_string() 
+    } else { 
+        "player1".to_string() 
     };
-    
-    to_c_string(winner.to_string())
 }
 
-#[no_mangle]
-pub extern "C" fn render(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
+/// Builds a state from a FEN, or from a PGN whose moves are replayed so that
+/// `moves` and `move_count` describe the whole game.
+fn state_from_position(input: &str) -> Result<GameState, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty position".to_string());
+    }
+
+    let looks_like_fen = !input.starts_with('[') && input.split_whitespace().next().is_some_and(|f| f.contains('/'));
+    if looks_like_fen {
+        return state_from_fen(input);
+    }
+
+    let game = pgn::parse(input)?;
+    let start_fen = game
+        .tags
+        .iter()
+        .find(|(tag, _)| tag == "FEN")
+        .map(|(_, fen)| fen.clone());
+    let mut state = match &start_fen {
+        Some(fen) => state_from_fen(fen)?,
+        None => state_from_fen(&to_fen(&Board::default(), 0, 1))?,
+    };
+
+    for (ply, san) in game.moves.iter().enumerate() {
+        let board = Board::from_str(&state.fen).unwrap_or_default();
+        match parse_move(&board, san) {
+            Ok(chess_move) => play(&mut state, &board, chess_move),
+            Err(error) => return Err(format!("move '{}' at ply {}: {}", san, ply + 1, error.message())),
+        }
+    }
+
+    Ok(state)
+}
+
+fn state_from_fen(fen: &str) -> Result<GameState, String> {
+    let board = parse_fen(fen).ok_or_else(|| format!("invalid FEN '{}'", fen))?;
+    let (halfmove_clock, fullmove_number) = fen_counters(fen);
+    let fen = to_fen(&board, halfmove_clock, fullmove_number);
+    let is_standard_start = fen == to_fen(&Board::default(), 0, 1);
+
+    Ok(GameState {
+        fen: fen.clone(),
+        moves: Vec::new(),
+        current_player: if board.side_to_move() == Color::White { "player1" } else { "player2" }.to_string(),
+        move_count: 0,
+        halfmove_clock,
+        fullmove_number,
+        start_fen: if is_standard_start { None } else { Some(fen) },
+        position: OnceCell::new(),
+    })
+}
+
+fn resets_halfmove_clock(board: &Board, chess_move: ChessMove) -> bool {
+    board.piece_on(chess_move.get_dest()).is_some()
+        || board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+}
+
+/// Neither side can ever deliver mate: bare kings, a single minor piece, or
+/// only bishops that all stand on squares of the same colour.
+fn has_insufficient_material(board: &Board) -> bool {
+    let heavy = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    if heavy.popcnt() > 0 {
+        return false;
+    }
+
+    let knights = board.pieces(Piece::Knight).popcnt();
+    let bishops = *board.pieces(Piece::Bishop);
+    if knights + bishops.popcnt() <= 1 {
+        return true;
+    }
+    if knights > 0 {
+        return false;
+    }
+
+    // Bit 0 is a1, a dark square, so the first rank's byte is 0xAA (b1, d1,
+    // f1, h1) and the second's 0x55 (a2, c2, e2, g2).
+    let light_squares = chess::BitBoard::new(0x55AA_55AA_55AA_55AA);
+    (bishops & light_squares).popcnt() == 0 || (bishops & !light_squares).popcnt() == 0
+}
+
+struct Chess;
+
+impl arena_game_sdk::Game for Chess {
+    type State = GameState;
+
+    const NAME: &'static str = "Chess";
+    const DESCRIPTION: &'static str = "Classic chess game with full rules";
+    const CAPABILITIES: Capabilities = Capabilities {
+        notation: true,
+        hints: true,
+        analysis: true,
+        draw_reasons: true,
+        custom_start: true,
+        ..Capabilities::BASIC
+    };
+
+    fn initial_state() -> GameState {
+        let board = Board::default();
         GameState {
-            fen: Board::default().to_string(),
+            fen: to_fen(&board, 0, 1),
             moves: Vec::new(),
             current_player: "player1".to_string(),
             move_count: 0,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            start_fen: None,
+            position: OnceCell::new(),
         }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
+    }
+
+    fn check_state(state: &GameState) -> Result<(), String> {
+        parse_fen(&state.fen).ok_or_else(|| format!("fen is not a legal position: {}", state.fen))?;
+        if let Some(start_fen) = &state.start_fen {
+            parse_fen(start_fen).ok_or_else(|| format!("start_fen is not a legal position: {}", start_fen))?;
+        }
+        Ok(())
+    }
+
+    fn legal_moves(state: &GameState) -> Vec<String> {
+        let position = state.position();
+        let mut moves = Vec::new();
+
+        if !position.is_game_over() {
+            for chess_move in MoveGen::new_legal(&position.board) {
+                moves.push(chess_move.to_string());
+            }
+        }
+        moves
+    }
+
+    fn apply(state: &mut GameState, move_str: &str) -> Result<(), GameError> {
+        let position = state.position();
+        if position.is_game_over() {
+            return Err(MoveError::GameOver.into());
+        }
+
+        let board = position.board;
+        let chess_move = parse_move(&board, move_str)?;
+        play(state, &board, chess_move);
+        Ok(())
+    }
+
+    fn outcome(state: &GameState) -> Outcome {
+        let position = state.position();
+
+        match position.board.status() {
+            chess::BoardStatus::Checkmate => {
+                if position.board.side_to_move() == Color::White {
+                    Outcome::Winner("player2".to_string())
+                } else {
+                    Outcome::Winner("player1".to_string())
+                }
+            },
+            _ if position.draw_reason().is_some() => Outcome::Draw,
+            _ => Outcome::Ongoing,
+        }
+    }
+
+    fn current_player(state: &GameState) -> String {
+        let board = Board::from_str(&state.fen).unwrap_or(Board::default());
+        let current_player = if board.side_to_move() == Color::White {
+            "player1"
+        } else {
+            "player2"
+        };
+
+        current_player.to_string()
+    }
+
+    fn render(state: &GameState) -> String {
+        let board = Board::from_str(&state.fen).unwrap_or(Board::default());
     
-    let mut output = String::new();
-    output.push_str("  a b c d e f g h\n");
+        let mut output = String::new();
+        output.push_str("  a b c d e f g h\n");
     
-    for rank in (0..8).rev() {
-        output.push_str(&format!("{} ", rank + 1));
-        for file in 0..8 {
-            let square = Square::make_square(chess::Rank::from_index(rank), chess::File::from_index(file));
-            let piece = board.piece_on(square);
-            let piece_char = match piece {
-                Some(piece) => {
-                    let color = board.color_on(square).unwrap();
-                    let symbol = match piece {
-                        Piece::Pawn => "P",
-                        Piece::Rook => "R",
-                        Piece::Knight => "N",
-                        Piece::Bishop => "B",
-                        Piece::Queen => "Q",
-                        Piece::King => "K",
-                    };
-                    if color == Color::White {
-                        symbol
-                    } else {
-                        match piece {
-                            Piece::Pawn => "p",
-                            Piece::Rook => "r",
-                            Piece::Knight => "n",
-                            Piece::Bishop => "b",
-                            Piece::Queen => "q",
-                            Piece::King => "k",
+        for rank in (0..8).rev() {
+            output.push_str(&format!("{} ", rank + 1));
+            for file in 0..8 {
+                let square = Square::make_square(chess::Rank::from_index(rank), chess::File::from_index(file));
+                let piece = board.piece_on(square).zip(board.color_on(square));
+                let piece_char = match piece {
+                    Some((piece, color)) => {
+                        let symbol = match piece {
+                            Piece::Pawn => "P",
+                            Piece::Rook => "R",
+                            Piece::Knight => "N",
+                            Piece::Bishop => "B",
+                            Piece::Queen => "Q",
+                            Piece::King => "K",
+                        };
+                        if color == Color::White {
+                            symbol
+                        } else {
+                            match piece {
+                                Piece::Pawn => "p",
+                                Piece::Rook => "r",
+                                Piece::Knight => "n",
+                                Piece::Bishop => "b",
+                                Piece::Queen => "q",
+                                Piece::King => "k",
+                            }
                         }
-                    }
-                },
-                None => "."
-            };
-            output.push_str(&format!("{} ", piece_char));
+                    },
+                    None => "."
+                };
+                output.push_str(&format!("{} ", piece_char));
+            }
+            output.push_str(&format!(" {}\n", rank + 1));
         }
-        output.push_str(&format!(" {}\n", rank + 1));
-    }
-    output.push_str("  a b c d e f g h\n");
+        output.push_str("  a b c d e f g h\n");
     
-    to_c_string(output)
-}
+        output
+    }
 
-#[no_mangle]
-pub extern "C" fn get_game_name() -> *mut c_char {
-    to_c_string("Chess".to_string())
-}
+    fn transcript(state: &GameState) -> String {
+        let mut transcript = String::new();
+        transcript.push_str("=== CHESS GAME TRANSCRIPT ===\n");
+        transcript.push_str(&format!("Move count: {}\n", state.move_count));
+        transcript.push_str(&format!("Current FEN: {}\n", state.fen));
+        transcript.push_str(&format!("Current player: {}\n", state.current_player));
 
-#[no_mangle]
-pub extern "C" fn get_current_player(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
+        let position = state.position();
+        if let Some(reason) = position.draw_reason() {
+            transcript.push_str(&format!("Draw: {}\n", reason.as_str()));
+        }
+        transcript.push_str("Moves played:\n");
+
+        let mut board = position.start;
+        for (i, move_str) in state.moves.iter().enumerate() {
+            match position.played.get(i) {
+                Some(chess_move) => {
+                    transcript.push_str(&format!("  {}. {} ({})\n", i + 1, move_str, san::to_san(&board, *chess_move)));
+                    board = board.make_move_new(*chess_move);
+                }
+                None => transcript.push_str(&format!("  {}. {}\n", i + 1, move_str)),
+            }
         }
-    });
 
+        let board = position.board;
+        let valid_moves: Vec<String> = MoveGen::new_legal(&board)
+            .map(|m| m.to_string())
+            .collect();
 
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    let current_player = if board.side_to_move() == Color::White {
-        "player1"
-    } else {
-        "player2"
-    };
+        transcript.push_str(&format!("Valid moves ({}):\n", valid_moves.len()));
+        for (i, move_str) in valid_moves.iter().enumerate() {
+            if i % 8 == 0 { transcript.push_str("  "); }
+            transcript.push_str(&format!("{:6}", move_str));
+            if (i + 1) % 8 == 0 { transcript.push('\n'); }
+        }
+        if !valid_moves.len().is_multiple_of(8) { transcript.push('\n'); }
 
-    to_c_string(current_player.to_string())
+        transcript.push_str("==============================\n");
+
+        transcript
+    }
 }
 
+arena_game_sdk::export_game!(Chess);
+arena_game_sdk::embed_metadata!("../metadata.json");
+
 #[no_mangle]
-pub extern "C" fn get_game_description() -> *mut c_char {
-    to_c_string("Classic chess game with full rules".to_string())
+pub extern "C" fn get_initial_state_from(position_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let position = from_c_string(position_ptr);
+
+        match state_from_position(&position) {
+            Ok(state) => write_state::<Chess>(&state),
+            Err(message) => to_c_string(GameError::new("invalid_position", message).to_result()),
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn get_draw_reason(state_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        to_c_result(read_state::<Chess>(state_ptr).map(|state| {
+            let reason = state.position().draw_reason();
+            reason.map(|r| r.as_str()).unwrap_or("").to_string()
+        }))
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn get_fen(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-    
-    to_c_string(state.fen)
+    guard(|| {
+        to_c_result(read_state::<Chess>(state_ptr).map(|state| state.fen))
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn is_check(state_ptr: *const c_char) -> i32 {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    if board.checkers().popcnt() > 0 { 1 } else { 0 }
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(_) => return -1,
+        };
+
+        let board = Board::from_str(&state.fen).unwrap_or(Board::default());
+        if board.checkers().popcnt() > 0 { 1 } else { 0 }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn is_checkmate(state_ptr: *const c_char) -> i32 {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    if board.status() == chess::BoardStatus::Checkmate { 1 } else { 0 }
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(_) => return -1,
+        };
+
+        let board = Board::from_str(&state.fen).unwrap_or(Board::default());
+        if board.status() == chess::BoardStatus::Checkmate { 1 } else { 0 }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn is_stalemate(state_ptr: *const c_char) -> i32 {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-    
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    if board.status() == chess::BoardStatus::Stalemate { 1 } else { 0 }
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(_) => return -1,
+        };
+
+        let board = Board::from_str(&state.fen).unwrap_or(Board::default());
+        if board.status() == chess::BoardStatus::Stalemate { 1 } else { 0 }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn get_move_uci(move_ptr: *const c_char) -> *mut c_char {
-    let move_str = from_c_string(move_ptr);
-    to_c_string(move_str)
+pub extern "C" fn get_move_notation(state_ptr: *const c_char, move_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let move_str = from_c_string(move_ptr);
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        let board = Board::from_str(&state.fen).unwrap_or(Board::default());
+        let notation = match parse_move(&board, &move_str) {
+            Ok(chess_move) => san::to_san(&board, chess_move),
+            Err(error) => GameError::from(error).to_result(),
+        };
+
+        to_c_string(notation)
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn log_transcript(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            fen: Board::default().to_string(),
-            moves: Vec::new(),
-            current_player: "player1".to_string(),
-            move_count: 0,
-        }
-    });
-
-    let mut transcript = String::new();
-    transcript.push_str(&format!("=== CHESS GAME TRANSCRIPT ===\n"));
-    transcript.push_str(&format!("Move count: {}\n", state.move_count));
-    transcript.push_str(&format!("Current FEN: {}\n", state.fen));
-    transcript.push_str(&format!("Current player: {}\n", state.current_player));
-    transcript.push_str(&format!("Moves played:\n"));
+pub extern "C" fn get_pgn(state_ptr: *const c_char, headers_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        // Headers are optional: a null pointer or anything but a JSON object
+        // leaves the default tags in place.
+        let headers = if headers_ptr.is_null() {
+            serde_json::Map::new()
+        } else {
+            serde_json::from_str(&from_c_string(headers_ptr)).unwrap_or_default()
+        };
+
+        to_c_string(state.position().to_pgn(&headers))
+    })
+}
 
-    for (i, move_str) in state.moves.iter().enumerate() {
-        transcript.push_str(&format!("  {}. {}\n", i + 1, move_str));
-    }
+/// Picks a move with the built-in engine. `depth_or_millis` up to 32 is a
+/// search depth, larger values a budget in milliseconds counted in nodes
+/// (see `engine::Limits`); `skill` runs from 0 (near-random) to 20 (full
+/// strength).
+#[no_mangle]
+pub extern "C" fn get_best_move(state_ptr: *const c_char, depth_or_millis: u32, skill: u32) -> *mut c_char {
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        let position = state.position();
+        if position.is_game_over() {
+            let error = MoveError::GameOver;
+            return to_c_string(GameError::from(error).to_result());
+        }
 
-    let board = Board::from_str(&state.fen).unwrap_or(Board::default());
-    let valid_moves: Vec<String> = MoveGen::new_legal(&board)
-        .map(|m| m.to_string())
-        .collect();
+        let limits = engine::Limits::from_depth_or_millis(depth_or_millis);
+        let result = match engine::search(&position.board, &position.history, position.halfmove_clock, limits, skill) {
+            Some(result) => result,
+            None => {
+                let error = MoveError::GameOver;
+                return to_c_string(GameError::from(error).to_result());
+            }
+        };
+
+        host_log!(
+            Level::Info,
+            "engine: {} at depth {} ({} nodes, score {})",
+            result.best_move,
+            result.depth,
+            result.nodes,
+            result.score
+        );
+
+        let output = serde_json::json!({
+            "move": result.best_move.to_string(),
+            "san": san::to_san(&position.board, result.best_move),
+            "score": result.score,
+            "mate": result.mate_in(),
+            "depth": result.depth,
+            "nodes": result.nodes,
+        });
+
+        to_c_string(output.to_string())
+    })
+}
 
-    transcript.push_str(&format!("Valid moves ({}):\n", valid_moves.len()));
-    for (i, move_str) in valid_moves.iter().enumerate() {
-        if i % 8 == 0 { transcript.push_str("  "); }
-        transcript.push_str(&format!("{:6}", move_str));
-        if (i + 1) % 8 == 0 { transcript.push_str("\n"); }
-    }
-    if valid_moves.len() % 8 != 0 { transcript.push_str("\n"); }
+/// Static evaluation of the current position in centipawns from White's
+/// point of view, with the terms it is made of. No search is done.
+#[no_mangle]
+pub extern "C" fn evaluate(state_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        let board = Board::from_str(&state.fen).unwrap_or_default();
+        let terms = eval::evaluate_terms(&board);
+
+        let output = serde_json::json!({
+            "score": terms.total(),
+            "material": terms.material,
+            "placement": terms.placement,
+            "mobility": terms.mobility,
+            "king_safety": terms.king_safety,
+            "pawn_structure": terms.pawn_structure,
+        });
+
+        to_c_string(output.to_string())
+    })
+}
 
-    transcript.push_str("==============================\n");
+/// Grades every move of the game against the built-in engine at full
+/// strength: centipawn loss per move, blunder/mistake/inaccuracy tags and
+/// per-player averages. `depth_or_millis` applies to each position, as for
+/// `get_best_move`.
+#[no_mangle]
+pub extern "C" fn analyze_game(state_ptr: *const c_char, depth_or_millis: u32) -> *mut c_char {
+    guard(|| {
+        let state = match read_state::<Chess>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        let position = state.position();
+        let start_halfmove = fen_counters(&position.start_fen).0;
+        let limits = engine::Limits::from_depth_or_millis(depth_or_millis);
+        let report = analysis::analyze(position.start, start_halfmove, &position.played, limits);
+
+        to_c_string(serde_json::to_string(&report).unwrap_or_default())
+    })
+}
 
-    to_c_string(transcript)
+#[no_mangle]
+pub extern "C" fn get_move_uci(move_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let move_str = from_c_string(move_ptr);
+        to_c_string(move_str)
+    })
 }
+
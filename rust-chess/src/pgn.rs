@@ -0,0 +1,264 @@
+use chess::{Board, ChessMove, Color};
+
+use crate::san;
+
+/// The Seven Tag Roster, in the order the PGN standard requires.
+const ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+const MAX_LINE_LENGTH: usize = 79;
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub struct PgnGame<'a> {
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub start_fullmove: u32,
+    pub moves: &'a [ChessMove],
+    pub result: &'a str,
+    pub comment: Option<String>,
+}
+
+impl PgnGame<'_> {
+    /// Renders the game in PGN export format: roster tags first, any other
+    /// tags after them, then SAN movetext wrapped at 79 columns.
+    pub fn write(&self) -> String {
+        let mut output = String::new();
+
+        for name in ROSTER {
+            let value = self
+                .tags
+                .iter()
+                .find(|(tag, _)| tag == name)
+                .map(|(_, value)| value.as_str())
+                .unwrap_or("?");
+            output.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+        }
+        for (name, value) in &self.tags {
+            if !ROSTER.contains(&name.as_str()) {
+                output.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+            }
+        }
+        output.push('\n');
+
+        let mut tokens = Vec::new();
+        let mut board = self.start;
+        let mut fullmove = self.start_fullmove;
+
+        for (i, chess_move) in self.moves.iter().enumerate() {
+            if board.side_to_move() == Color::White {
+                tokens.push(format!("{}.", fullmove));
+            } else if i == 0 {
+                tokens.push(format!("{}...", fullmove));
+            }
+            tokens.push(san::to_san(&board, *chess_move));
+
+            if board.side_to_move() == Color::Black {
+                fullmove += 1;
+            }
+            board = board.make_move_new(*chess_move);
+        }
+
+        if let Some(comment) = &self.comment {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                output.push_str(&line);
+                output.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        output.push_str(&line);
+        output.push('\n');
+
+        output
+    }
+}
+
+pub struct ParsedPgn {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+}
+
+/// Reads the first game of a PGN: its tag pairs and the SAN tokens of the
+/// main line. Comments, variations, NAGs, move numbers and the result
+/// token are skipped, and a tag line after the movetext, which starts the
+/// next game, ends the input.
+pub fn parse(text: &str) -> Result<ParsedPgn, String> {
+    let mut tags = Vec::new();
+    let mut movetext = String::new();
+    let mut in_comment = false;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && !in_comment {
+            if !movetext.trim().is_empty() {
+                break;
+            }
+            tags.push(parse_tag(line)?);
+        } else if !line.starts_with('%') {
+            in_comment = ends_in_comment(in_comment, line);
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    let mut moves = Vec::new();
+    let mut token = String::new();
+    let mut chars = movetext.chars();
+    let mut variation_depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                if !chars.by_ref().any(|c| c == '}') {
+                    return Err("unterminated comment in PGN".to_string());
+                }
+            }
+            ';' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '(' => variation_depth += 1,
+            ')' if variation_depth == 0 => return Err("unbalanced ')' in PGN".to_string()),
+            ')' => variation_depth -= 1,
+            c if c.is_whitespace() => {}
+            _ => {
+                token.push(c);
+                let ends = chars.clone().next().is_none_or(|next| next.is_whitespace() || "{;()".contains(next));
+                if ends {
+                    if variation_depth == 0 {
+                        if let Some(san) = movetext_san(&token) {
+                            moves.push(san);
+                        }
+                    }
+                    token.clear();
+                }
+            }
+        }
+    }
+    if variation_depth != 0 {
+        return Err("unterminated variation in PGN".to_string());
+    }
+
+    Ok(ParsedPgn { tags, moves })
+}
+
+/// Whether a `{...}` comment is still open after `line`, so a following
+/// line starting with '[' is comment text rather than a tag.
+fn ends_in_comment(in_comment: bool, line: &str) -> bool {
+    let mut in_comment = in_comment;
+    for c in line.chars() {
+        match c {
+            '{' if !in_comment => in_comment = true,
+            '}' if in_comment => in_comment = false,
+            ';' if !in_comment => break,
+            _ => {}
+        }
+    }
+    in_comment
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), String> {
+    let invalid = || format!("invalid PGN tag '{}'", line);
+    let inner = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')).ok_or_else(invalid)?;
+    let (name, value) = inner.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let value = value.trim().strip_prefix('"').and_then(|v| v.strip_suffix('"')).ok_or_else(invalid)?;
+    Ok((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+/// Strips a leading move number ("12." or "12...") from a movetext token and
+/// returns what is left if it is a move rather than a NAG or result.
+fn movetext_san(token: &str) -> Option<String> {
+    if token.starts_with('$') || matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+        return None;
+    }
+    let number_len = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let san = if number_len > 0 && token[number_len..].starts_with('.') {
+        token[number_len..].trim_start_matches('.')
+    } else {
+        token
+    };
+    if san.is_empty() {
+        None
+    } else {
+        Some(san.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Square;
+
+    const SHUFFLE: [(Square, Square); 4] =
+        [(Square::G1, Square::F3), (Square::G8, Square::F6), (Square::F3, Square::G1), (Square::F6, Square::G8)];
+
+    fn shuffle(plies: usize) -> Vec<ChessMove> {
+        SHUFFLE.iter().cycle().take(plies).map(|&(source, dest)| ChessMove::new(source, dest, None)).collect()
+    }
+
+    fn game<'a>(moves: &'a [ChessMove], tags: Vec<(String, String)>) -> PgnGame<'a> {
+        PgnGame { tags, start: Board::default(), start_fullmove: 1, moves, result: "*", comment: None }
+    }
+
+    #[test]
+    fn wraps_movetext_at_79_columns() {
+        let moves = shuffle(60);
+        let written = game(&moves, Vec::new()).write();
+        let movetext: Vec<&str> = written.split("\n\n").nth(1).unwrap().lines().collect();
+
+        assert!(movetext.len() > 1);
+        assert!(movetext.iter().all(|line| line.len() <= MAX_LINE_LENGTH), "{}", written);
+        assert!(movetext[0].starts_with("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3"));
+        assert!(movetext.last().unwrap().ends_with("30. Ng1 Ng8 *"));
+        assert_eq!(parse(&written).unwrap().moves.len(), 60);
+    }
+
+    #[test]
+    fn writes_missing_roster_tags_as_unknown_and_escapes_values() {
+        let tags = vec![("Annotator".to_string(), r#"say "hi" \ bye"#.to_string())];
+        let written = game(&[], tags).write();
+        assert_eq!(
+            written,
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"?\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"?\"]\n\
+             [Annotator \"say \\\"hi\\\" \\\\ bye\"]\n\n*\n"
+        );
+        assert_eq!(parse(&written).unwrap().tags[7].1, r#"say "hi" \ bye"#);
+    }
+
+    #[test]
+    fn reads_only_the_main_line() {
+        let text = "[Event \"Test\"]\n\
+                    % an escaped line\n\
+                    1. e4 $1 e5!? {a comment\n\
+                    [that looks like a tag]} 2. Nf3 (2. f4 exf4 (2... d5)) 2... Nc6 ; rest of line\n\
+                    3.Bb5 1-0\n";
+        let parsed = parse(text).unwrap();
+        assert_eq!(parsed.tags, vec![("Event".to_string(), "Test".to_string())]);
+        assert_eq!(parsed.moves, vec!["e4", "e5!?", "Nf3", "Nc6", "Bb5"]);
+    }
+
+    #[test]
+    fn stops_at_the_next_game() {
+        let parsed = parse("[Event \"One\"]\n\n1. d4 *\n\n[Event \"Two\"]\n\n1. c4 *\n").unwrap();
+        assert_eq!(parsed.tags.len(), 1);
+        assert_eq!(parsed.moves, vec!["d4"]);
+    }
+
+    #[test]
+    fn rejects_malformed_movetext_and_tags() {
+        assert_eq!(parse("1. e4 ) e5").err().unwrap(), "unbalanced ')' in PGN");
+        assert_eq!(parse("1. e4 (1. d4").err().unwrap(), "unterminated variation in PGN");
+        assert_eq!(parse("1. e4 {never closed").err().unwrap(), "unterminated comment in PGN");
+        assert_eq!(parse("[Event Test]").err().unwrap(), "invalid PGN tag '[Event Test]'");
+    }
+}
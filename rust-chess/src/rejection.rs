@@ -0,0 +1,300 @@
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_pawn_quiets, get_rook_moves, BitBoard,
+    Board, ChessMove, Color, MoveGen, Piece, Square, EMPTY,
+};
+use std::str::FromStr;
+
+use arena_game_sdk::GameError;
+
+/// Why `apply_move` refused a move. Sent back to the host inside the
+/// `ERROR:` result so the agent that made the move can be told what was
+/// wrong with it.
+#[derive(Debug, PartialEq)]
+pub enum MoveError {
+    Unparseable(String),
+    NoPiece(Square),
+    WrongColor(Square),
+    PinnedPiece(Square),
+    LeavesKingInCheck,
+    Ambiguous(String),
+    Illegal(String),
+    GameOver,
+}
+
+impl MoveError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            MoveError::Unparseable(_) => "unparseable",
+            MoveError::NoPiece(_) => "no_piece",
+            MoveError::WrongColor(_) => "wrong_color",
+            MoveError::PinnedPiece(_) => "pinned_piece",
+            MoveError::LeavesKingInCheck => "leaves_king_in_check",
+            MoveError::Ambiguous(_) => "ambiguous",
+            MoveError::Illegal(_) => "illegal_move",
+            MoveError::GameOver => "game_over",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            MoveError::Unparseable(input) => {
+                format!("'{}' is not a move in UCI (e.g. g1f3) or SAN (e.g. Nf3) notation", input)
+            }
+            MoveError::NoPiece(square) => format!("there is no piece on {}", square),
+            MoveError::WrongColor(square) => format!("the piece on {} belongs to the opponent", square),
+            MoveError::PinnedPiece(square) => {
+                format!("the piece on {} is pinned and cannot leave the line to its king", square)
+            }
+            MoveError::LeavesKingInCheck => "the move would leave the king in check".to_string(),
+            MoveError::Ambiguous(input) => {
+                format!("'{}' matches more than one legal move; name the origin file or rank", input)
+            }
+            MoveError::Illegal(reason) => reason.clone(),
+            MoveError::GameOver => "the game is already over".to_string(),
+        }
+    }
+}
+
+impl From<MoveError> for GameError {
+    fn from(error: MoveError) -> GameError {
+        GameError::new(error.code(), error.message())
+    }
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "pawn",
+        Piece::Knight => "knight",
+        Piece::Bishop => "bishop",
+        Piece::Rook => "rook",
+        Piece::Queen => "queen",
+        Piece::King => "king",
+    }
+}
+
+/// Squares the piece on `square` could reach if its own king's safety
+/// were ignored. Castling is not included.
+fn pseudo_targets(board: &Board, square: Square) -> BitBoard {
+    let (piece, color) = match (board.piece_on(square), board.color_on(square)) {
+        (Some(piece), Some(color)) => (piece, color),
+        _ => return EMPTY,
+    };
+    let blockers = *board.combined();
+    let targets = match piece {
+        Piece::Pawn => {
+            let mut victims = *board.color_combined(!color);
+            if let Some(ep) = board.en_passant() {
+                victims |= BitBoard::from_square(ep.uforward(color));
+            }
+            get_pawn_attacks(square, color, victims) | get_pawn_quiets(square, color, blockers)
+        }
+        Piece::Knight => get_knight_moves(square),
+        Piece::Bishop => get_bishop_moves(square, blockers),
+        Piece::Rook => get_rook_moves(square, blockers),
+        Piece::Queen => get_bishop_moves(square, blockers) | get_rook_moves(square, blockers),
+        Piece::King => get_king_moves(square),
+    };
+    targets & !board.color_combined(color)
+}
+
+/// A piece that could reach its target but may not actually move there.
+fn self_check_reason(board: &Board, source: Square) -> MoveError {
+    if (board.pinned() & BitBoard::from_square(source)) != EMPTY {
+        MoveError::PinnedPiece(source)
+    } else {
+        MoveError::LeavesKingInCheck
+    }
+}
+
+/// Explains why a UCI move that `board.legal()` refused is illegal.
+fn explain_uci(board: &Board, chess_move: ChessMove) -> MoveError {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+
+    let piece = match board.piece_on(source) {
+        Some(piece) => piece,
+        None => return MoveError::NoPiece(source),
+    };
+    if board.color_on(source) != Some(board.side_to_move()) {
+        return MoveError::WrongColor(source);
+    }
+
+    if piece == Piece::King && source.get_file().to_index().abs_diff(dest.get_file().to_index()) == 2 {
+        return if board.checkers().popcnt() > 0 {
+            MoveError::Illegal("cannot castle out of check".to_string())
+        } else {
+            MoveError::Illegal("castling is not allowed in this position".to_string())
+        };
+    }
+
+    if (pseudo_targets(board, source) & BitBoard::from_square(dest)) == EMPTY {
+        return MoveError::Illegal(format!("the {} on {} cannot move to {}", piece_name(piece), source, dest));
+    }
+
+    let last_rank = if board.side_to_move() == Color::White { 7 } else { 0 };
+    let promotes = piece == Piece::Pawn && dest.get_rank().to_index() == last_rank;
+    match chess_move.get_promotion() {
+        None if promotes => {
+            return MoveError::Illegal(format!(
+                "a pawn reaching {} must promote; add the piece, e.g. {}{}q",
+                dest, source, dest
+            ))
+        }
+        Some(_) if !promotes => return MoveError::Illegal(format!("the move {}{} is not a promotion", source, dest)),
+        Some(Piece::Pawn) | Some(Piece::King) => {
+            return MoveError::Illegal("a pawn can only promote to a queen, rook, bishop or knight".to_string())
+        }
+        _ => {}
+    }
+
+    self_check_reason(board, source)
+}
+
+/// Explains why a SAN move matched no legal move, by looking at which of
+/// the named pieces could reach the destination if checks were ignored.
+fn explain_san(board: &Board, move_str: &str) -> MoveError {
+    let unparseable = || MoveError::Unparseable(move_str.to_string());
+    let san: String = move_str
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, 'x' | ':' | '+' | '#' | '!' | '?'))
+        .collect();
+
+    if matches!(san.as_str(), "O-O" | "0-0" | "O-O-O" | "0-0-0") {
+        return if board.checkers().popcnt() > 0 {
+            MoveError::Illegal("cannot castle out of check".to_string())
+        } else {
+            MoveError::Illegal("castling is not allowed in this position".to_string())
+        };
+    }
+
+    let san = san.split('=').next().unwrap_or("").trim_end_matches(['Q', 'R', 'B', 'N']);
+    let (piece, rest) = match san.chars().next() {
+        Some('N') => (Piece::Knight, &san[1..]),
+        Some('B') => (Piece::Bishop, &san[1..]),
+        Some('R') => (Piece::Rook, &san[1..]),
+        Some('Q') => (Piece::Queen, &san[1..]),
+        Some('K') => (Piece::King, &san[1..]),
+        Some('a'..='h') => (Piece::Pawn, san),
+        _ => return unparseable(),
+    };
+    if rest.len() < 2 || !rest.is_char_boundary(rest.len() - 2) {
+        return unparseable();
+    }
+    let (hint, dest) = rest.split_at(rest.len() - 2);
+    let dest = match Square::from_str(dest) {
+        Ok(dest) => dest,
+        Err(_) => return unparseable(),
+    };
+    if hint.len() > 2 || !hint.chars().all(|c| matches!(c, 'a'..='h' | '1'..='8')) {
+        return unparseable();
+    }
+    // A pawn that does not capture stays on its file.
+    let pawn_file = dest.to_string()[..1].to_string();
+    let hint = if piece == Piece::Pawn && hint.is_empty() { pawn_file.as_str() } else { hint };
+
+    let last_rank = if board.side_to_move() == Color::White { 7 } else { 0 };
+    if piece == Piece::Pawn && dest.get_rank().to_index() == last_rank && !move_str.contains('=') {
+        let promoted = move_str.trim_end_matches(['+', '#', '!', '?']);
+        if !promoted.ends_with(['Q', 'R', 'B', 'N']) {
+            return MoveError::Illegal(format!("a pawn reaching {} must promote, e.g. {}=Q", dest, dest));
+        }
+    }
+
+    let own = board.color_combined(board.side_to_move()) & board.pieces(piece);
+    let candidates: Vec<Square> = own
+        .filter(|source| hint.chars().all(|c| source.to_string().contains(c)))
+        .filter(|source| (pseudo_targets(board, *source) & BitBoard::from_square(dest)) != EMPTY)
+        .collect();
+
+    let legal = MoveGen::new_legal(board)
+        .filter(|m| candidates.contains(&m.get_source()) && m.get_dest() == dest)
+        .count();
+    if legal > 1 {
+        return MoveError::Ambiguous(move_str.to_string());
+    }
+
+    match candidates.first() {
+        None if own.popcnt() == 0 => MoveError::Illegal(format!("there is no {} that can move", piece_name(piece))),
+        None => MoveError::Illegal(format!("no {} can move to {}", piece_name(piece), dest)),
+        Some(_) if legal == 1 => MoveError::Illegal(format!("'{}' is not valid here", move_str)),
+        Some(source) => self_check_reason(board, *source),
+    }
+}
+
+/// Diagnoses a move string that did not resolve to a legal move.
+pub fn explain(board: &Board, move_str: &str) -> MoveError {
+    match ChessMove::from_str(move_str.trim()) {
+        Ok(chess_move) => explain_uci(board, chess_move),
+        Err(_) => explain_san(board, move_str),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PINNED_KNIGHT: &str = "4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1";
+    const TWO_KNIGHTS: &str = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+    const ROOK_ON_SECOND: &str = "4k3/8/8/8/8/8/3r4/4K3 w - - 0 1";
+    const PAWN_ON_SEVENTH: &str = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+
+    fn explain_in(fen: &str, move_str: &str) -> MoveError {
+        explain(&Board::from_str(fen).unwrap(), move_str)
+    }
+
+    fn explain_at_start(move_str: &str) -> MoveError {
+        explain(&Board::default(), move_str)
+    }
+
+    fn illegal(reason: &str) -> MoveError {
+        MoveError::Illegal(reason.to_string())
+    }
+
+    #[test]
+    fn names_the_square_for_missing_and_enemy_pieces() {
+        assert_eq!(explain_at_start("e3e4"), MoveError::NoPiece(Square::E3));
+        assert_eq!(explain_at_start("e7e5"), MoveError::WrongColor(Square::E7));
+    }
+
+    #[test]
+    fn tells_a_pin_from_walking_into_check() {
+        assert_eq!(explain_in(PINNED_KNIGHT, "e2c3"), MoveError::PinnedPiece(Square::E2));
+        assert_eq!(explain_in(PINNED_KNIGHT, "Nc3"), MoveError::PinnedPiece(Square::E2));
+        assert_eq!(explain_in(ROOK_ON_SECOND, "e1e2"), MoveError::LeavesKingInCheck);
+        assert_eq!(explain_in(ROOK_ON_SECOND, "Kf2"), MoveError::LeavesKingInCheck);
+    }
+
+    #[test]
+    fn explains_moves_no_piece_can_make() {
+        assert_eq!(explain_at_start("e2e5"), illegal("the pawn on e2 cannot move to e5"));
+        assert_eq!(explain_at_start("Nf6"), illegal("no knight can move to f6"));
+        assert_eq!(explain_at_start("e1g1"), illegal("castling is not allowed in this position"));
+        assert_eq!(explain_at_start("O-O"), illegal("castling is not allowed in this position"));
+        assert_eq!(explain_in(ROOK_ON_SECOND, "Qd2"), illegal("there is no queen that can move"));
+    }
+
+    #[test]
+    fn asks_for_a_promotion_piece() {
+        assert_eq!(explain_in(PAWN_ON_SEVENTH, "a7a8"), illegal("a pawn reaching a8 must promote; add the piece, e.g. a7a8q"));
+        assert_eq!(explain_in(PAWN_ON_SEVENTH, "a8"), illegal("a pawn reaching a8 must promote, e.g. a8=Q"));
+        assert_eq!(explain_at_start("e2e4q"), illegal("the move e2e4 is not a promotion"));
+    }
+
+    #[test]
+    fn flags_ambiguous_and_unreadable_input() {
+        assert_eq!(explain_in(TWO_KNIGHTS, "Nd2"), MoveError::Ambiguous("Nd2".to_string()));
+        for input in ["hello", "Zz9", "N", ""] {
+            assert_eq!(explain_at_start(input), MoveError::Unparseable(input.to_string()), "{}", input);
+        }
+    }
+
+    #[test]
+    fn codes_are_stable() {
+        let error = GameError::from(MoveError::PinnedPiece(Square::E2));
+        assert_eq!(error.code, "pinned_piece");
+        assert_eq!(error.message, "the piece on e2 is pinned and cannot leave the line to its king");
+        assert_eq!(MoveError::Illegal(String::new()).code(), "illegal_move");
+        assert_eq!(MoveError::LeavesKingInCheck.code(), "leaves_king_in_check");
+    }
+}
@@ -0,0 +1,211 @@
+use chess::{Board, BoardStatus, ChessMove, MoveGen, Piece, Square};
+
+fn piece_letter(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "",
+        Piece::Knight => "N",
+        Piece::Bishop => "B",
+        Piece::Rook => "R",
+        Piece::Queen => "Q",
+        Piece::King => "K",
+    }
+}
+
+fn file_char(square: Square) -> char {
+    (b'a' + square.get_file().to_index() as u8) as char
+}
+
+fn rank_char(square: Square) -> char {
+    (b'1' + square.get_rank().to_index() as u8) as char
+}
+
+fn is_castle(board: &Board, chess_move: ChessMove) -> bool {
+    board.piece_on(chess_move.get_source()) == Some(Piece::King)
+        && chess_move.get_source().get_file().to_index().abs_diff(chess_move.get_dest().get_file().to_index()) == 2
+}
+
+fn is_capture(board: &Board, chess_move: ChessMove) -> bool {
+    board.piece_on(chess_move.get_dest()).is_some()
+        || (board.piece_on(chess_move.get_source()) == Some(Piece::Pawn)
+            && chess_move.get_source().get_file() != chess_move.get_dest().get_file())
+}
+
+/// SAN without the check suffix, e.g. "Nbd7", "exd5", "e8=Q", "O-O".
+fn san_body(board: &Board, chess_move: ChessMove) -> String {
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    let piece = board.piece_on(source).unwrap_or(Piece::Pawn);
+
+    if is_castle(board, chess_move) {
+        return if dest.get_file().to_index() > source.get_file().to_index() {
+            "O-O".to_string()
+        } else {
+            "O-O-O".to_string()
+        };
+    }
+
+    let mut san = String::new();
+    let capture = is_capture(board, chess_move);
+
+    if piece == Piece::Pawn {
+        if capture {
+            san.push(file_char(source));
+        }
+    } else {
+        san.push_str(piece_letter(piece));
+
+        let rivals: Vec<Square> = MoveGen::new_legal(board)
+            .filter(|m| m.get_dest() == dest && m.get_source() != source)
+            .filter(|m| board.piece_on(m.get_source()) == Some(piece))
+            .map(|m| m.get_source())
+            .collect();
+        if !rivals.is_empty() {
+            let same_file = rivals.iter().any(|s| s.get_file() == source.get_file());
+            let same_rank = rivals.iter().any(|s| s.get_rank() == source.get_rank());
+            if !same_file {
+                san.push(file_char(source));
+            } else if !same_rank {
+                san.push(rank_char(source));
+            } else {
+                san.push(file_char(source));
+                san.push(rank_char(source));
+            }
+        }
+    }
+
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&dest.to_string());
+
+    if let Some(promotion) = chess_move.get_promotion() {
+        san.push('=');
+        san.push_str(piece_letter(promotion));
+    }
+
+    san
+}
+
+/// Standard Algebraic Notation for a legal move, with "+" or "#" appended.
+pub fn to_san(board: &Board, chess_move: ChessMove) -> String {
+    let mut san = san_body(board, chess_move);
+    let next = board.make_move_new(chess_move);
+    if next.status() == BoardStatus::Checkmate {
+        san.push('#');
+    } else if next.checkers().popcnt() > 0 {
+        san.push('+');
+    }
+    san
+}
+
+/// Drops the decorations LLMs are inconsistent about: capture marks,
+/// promotion "=", check and annotation suffixes, and zeros for castling.
+fn normalize(san: &str) -> String {
+    san.trim()
+        .trim_end_matches("e.p.")
+        .chars()
+        .filter(|c| !matches!(c, 'x' | ':' | '=' | '+' | '#' | '!' | '?' | ' '))
+        .map(|c| if c == '0' { 'O' } else { c })
+        .collect()
+}
+
+/// Finds the legal move a SAN string refers to. Over-disambiguated input
+/// such as "Ng1f3" is accepted; ambiguous input is not.
+pub fn from_san(board: &Board, san: &str) -> Option<ChessMove> {
+    let wanted = normalize(san);
+    if wanted.is_empty() {
+        return None;
+    }
+
+    let mut found = None;
+    for chess_move in MoveGen::new_legal(board) {
+        let body = san_body(board, chess_move);
+        let source = chess_move.get_source();
+        let mut forms = vec![normalize(&body)];
+
+        if let Some(piece) = board.piece_on(source) {
+            if piece != Piece::Pawn && !is_castle(board, chess_move) {
+                let letter = piece_letter(piece);
+                let dest = chess_move.get_dest();
+                forms.push(format!("{}{}{}", letter, file_char(source), dest));
+                forms.push(format!("{}{}{}", letter, rank_char(source), dest));
+                forms.push(format!("{}{}{}", letter, source, dest));
+            }
+        }
+
+        if forms.contains(&wanted) {
+            if found.is_some() {
+                return None;
+            }
+            found = Some(chess_move);
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn board(fen: &str) -> Board {
+        Board::from_str(fen).unwrap()
+    }
+
+    fn san(fen: &str, source: Square, dest: Square, promotion: Option<Piece>) -> String {
+        to_san(&board(fen), ChessMove::new(source, dest, promotion))
+    }
+
+    const KNIGHTS: &str = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+    const ROOKS: &str = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    const QUEENS: &str = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+    const CASTLING: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    const PROMOTION: &str = "k7/4P3/1K6/8/8/8/8/8 w - - 0 1";
+    const EN_PASSANT: &str = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+
+    #[test]
+    fn disambiguates_by_file_then_rank_then_both() {
+        assert_eq!(san(KNIGHTS, Square::B1, Square::D2, None), "Nbd2");
+        assert_eq!(san(KNIGHTS, Square::F3, Square::E5, None), "Ne5");
+        assert_eq!(san(ROOKS, Square::A1, Square::A3, None), "R1a3");
+        assert_eq!(san(QUEENS, Square::A1, Square::B2, None), "Qa1b2");
+    }
+
+    #[test]
+    fn writes_castling_promotion_and_en_passant() {
+        assert_eq!(san(CASTLING, Square::E1, Square::G1, None), "O-O");
+        assert_eq!(san(CASTLING, Square::E1, Square::C1, None), "O-O-O");
+        assert_eq!(san(PROMOTION, Square::E7, Square::E8, Some(Piece::Queen)), "e8=Q#");
+        assert_eq!(san(PROMOTION, Square::E7, Square::E8, Some(Piece::Knight)), "e8=N");
+        assert_eq!(san(EN_PASSANT, Square::E5, Square::D6, None), "exd6");
+    }
+
+    #[test]
+    fn marks_check() {
+        assert_eq!(san(ROOKS, Square::A5, Square::A8, None), "Ra8+");
+    }
+
+    #[test]
+    fn reads_sloppy_and_over_disambiguated_input() {
+        let start = Board::default();
+        let nf3 = Some(ChessMove::new(Square::G1, Square::F3, None));
+        for input in ["Nf3", "Ngf3", "N1f3", "Ng1f3", "Nf3+", "Nf3!?", " Nf3 "] {
+            assert_eq!(from_san(&start, input), nf3, "{}", input);
+        }
+
+        let castling = board(CASTLING);
+        assert_eq!(from_san(&castling, "0-0-0"), Some(ChessMove::new(Square::E1, Square::C1, None)));
+        assert_eq!(from_san(&board(PROMOTION), "e8Q"), Some(ChessMove::new(Square::E7, Square::E8, Some(Piece::Queen))));
+        assert_eq!(from_san(&board(EN_PASSANT), "exd6 e.p."), Some(ChessMove::new(Square::E5, Square::D6, None)));
+    }
+
+    #[test]
+    fn refuses_ambiguous_and_unknown_input() {
+        assert_eq!(from_san(&board(KNIGHTS), "Nd2"), None);
+        assert_eq!(from_san(&board(QUEENS), "Qb2"), None);
+        assert_eq!(from_san(&Board::default(), "e5"), None);
+        assert_eq!(from_san(&Board::default(), ""), None);
+        assert_eq!(from_san(&Board::default(), "+"), None);
+    }
+}
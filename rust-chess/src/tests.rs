@@ -0,0 +1,413 @@
+//! Games played through the exports, as the host plays them. The last
+//! section plays several games on one instance at once: every result has
+//! to come from the state passed in, never from whatever game the instance
+//! touched last.
+
+use std::thread;
+
+use arena_game_sdk::ffi::test_util::{c, play, take};
+
+use super::*;
+
+fn winner(state: &str) -> String {
+    take(get_winner(c(state).as_ptr()))
+}
+
+fn fen(state: &str) -> String {
+    take(get_fen(c(state).as_ptr()))
+}
+
+fn import(position: &str) -> String {
+    let state = take(get_initial_state_from(c(position).as_ptr()));
+    assert!(!state.starts_with("ERROR:"), "{} rejected: {}", position, state);
+    state
+}
+
+fn draw_reason(state: &str) -> String {
+    take(get_draw_reason(c(state).as_ptr()))
+}
+
+// Rejections
+
+fn rejection(state: &str, move_str: &str) -> String {
+    let result = take(apply_move(c(state).as_ptr(), c(move_str).as_ptr()));
+    assert!(result.starts_with("ERROR:"), "{} accepted: {}", move_str, result);
+    result
+}
+
+#[test]
+fn a_rejected_move_reports_its_code_and_reason() {
+    let start = take(get_initial_state());
+    let result = rejection(&start, "e2e5");
+    assert!(result.contains("\"code\":\"illegal_move\""), "{}", result);
+    assert!(result.contains("the pawn on e2 cannot move to e5"), "{}", result);
+
+    assert!(rejection(&start, "e7e5").contains("\"code\":\"wrong_color\""));
+    assert!(rejection(&start, "castle").contains("\"code\":\"unparseable\""));
+}
+
+#[test]
+fn no_move_is_accepted_once_the_game_is_over() {
+    let mated = play_alone(&FOOLS_MATE);
+    assert!(rejection(&mated, "e2e4").contains("\"code\":\"game_over\""));
+}
+
+// Draws
+
+#[test]
+fn bare_kings_and_same_coloured_bishops_are_insufficient_material() {
+    for fen in [
+        "8/8/8/4k3/8/8/8/4K3 w - - 0 1",
+        "8/8/8/4k3/8/8/8/2B1K3 w - - 0 1",
+        "8/8/8/4k3/8/2b5/8/2B1K3 w - - 0 1",
+    ] {
+        let state = import(fen);
+        assert_eq!(draw_reason(&state), "insufficient_material", "{}", fen);
+        assert_eq!(winner(&state), "draw");
+    }
+
+    for fen in [
+        "8/8/8/4k3/8/2b5/8/3BK3 w - - 0 1",
+        "8/8/8/4k3/8/8/8/1NN1K3 w - - 0 1",
+        "8/8/8/4k3/8/8/4P3/4K3 w - - 0 1",
+    ] {
+        let state = import(fen);
+        assert_eq!(draw_reason(&state), "", "{}", fen);
+        assert_eq!(winner(&state), "");
+    }
+}
+
+#[test]
+fn the_hundredth_quiet_half_move_draws() {
+    let state = import("8/8/8/4k3/8/8/R7/4K3 w - - 99 80");
+    assert_eq!(draw_reason(&state), "");
+
+    let drawn = play(apply_move, &state, "a2a3");
+    assert_eq!(draw_reason(&drawn), "fifty_move_rule");
+    assert_eq!(take(get_valid_moves(c(&drawn).as_ptr())), "[]");
+}
+
+#[test]
+fn stalemate_is_a_draw() {
+    let state = import("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+    assert_eq!(draw_reason(&state), "stalemate");
+    assert_eq!(winner(&state), "draw");
+    assert_eq!(is_stalemate(c(&state).as_ptr()), 1);
+}
+
+#[test]
+fn a_position_seen_twice_is_not_yet_a_draw() {
+    let state = play_alone(&REPETITION[..4]);
+    assert_eq!(draw_reason(&state), "");
+    assert_eq!(draw_reason(&play_alone(&REPETITION)), "threefold_repetition");
+}
+
+#[test]
+fn checkmate_has_no_draw_reason() {
+    let state = play_alone(&FOOLS_MATE);
+    assert_eq!(draw_reason(&state), "");
+    assert_eq!(winner(&state), "player2");
+}
+
+// FEN
+
+#[test]
+fn fen_records_the_square_behind_a_double_stepped_pawn() {
+    let state = play_alone(&["e2e4", "a7a6", "e4e5", "d7d5"]);
+    assert_eq!(fen(&state), "rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3");
+
+    let state = import("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3");
+    assert_eq!(fen(&state), "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3");
+    let pgn = take(get_pgn(c(&state).as_ptr(), std::ptr::null()));
+    assert!(pgn.contains("[FEN \"rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3\"]"), "{}", pgn);
+}
+
+#[test]
+fn fen_leaves_out_an_en_passant_square_nobody_can_use() {
+    let state = play_alone(&["e2e4"]);
+    assert_eq!(fen(&state), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+}
+
+#[test]
+fn a_fen_without_both_kings_is_refused() {
+    for position in ["8/8/8 w - - 0 1", "8/8/8/8/8/8/8/8 w - - 0 1", "4k3/8/8/8/8/8/8/8 w - - 0 1"] {
+        let result = take(get_initial_state_from(c(position).as_ptr()));
+        assert!(result.starts_with("ERROR:"), "{}", result);
+        assert!(result.contains("\"code\":\"invalid_position\""), "{}", result);
+    }
+
+    let state = r#"{"fen":"4k3/8/8/8/8/8/8/8 w - - 0 1","moves":[],"current_player":"player1","move_count":0}"#;
+    let result = take(get_valid_moves(c(state).as_ptr()));
+    assert!(result.contains("\"code\":\"invalid_state\""), "{}", result);
+}
+
+#[test]
+fn fen_counts_quiet_half_moves_and_full_moves() {
+    let state = play_alone(&["g1f3"]);
+    assert!(fen(&state).ends_with(" b KQkq - 1 1"), "{}", fen(&state));
+    let state = play(apply_move, &state, "g8f6");
+    assert!(fen(&state).ends_with(" w KQkq - 2 2"), "{}", fen(&state));
+    let state = play(apply_move, &state, "e2e4");
+    assert!(fen(&state).ends_with(" b KQkq - 0 2"), "{}", fen(&state));
+    let state = play(apply_move, &state, "b8c6");
+    let state = play(apply_move, &state, "f1b5");
+    assert!(fen(&state).ends_with(" b KQkq - 2 3"), "{}", fen(&state));
+    let state = play(apply_move, &state, "f6e4");
+    assert!(fen(&state).ends_with(" w KQkq - 0 4"), "{}", fen(&state));
+}
+
+#[test]
+fn imported_counters_carry_on_from_the_fen() {
+    let state = import("8/8/8/4k3/8/8/R7/4K3 b - - 37 61");
+    let state = play(apply_move, &state, "e5d5");
+    assert!(fen(&state).ends_with(" w - - 38 62"), "{}", fen(&state));
+
+    // A wasm32 build aborts on overflow panics, so the count stops instead.
+    let state = import("8/8/8/4k3/8/8/R7/4K3 b - - 0 4294967295");
+    let state = play(apply_move, &state, "e5d5");
+    assert!(fen(&state).ends_with(" w - - 1 4294967295"), "{}", fen(&state));
+}
+
+// PGN
+
+fn pgn(state: &str, headers: Option<&str>) -> String {
+    match headers {
+        Some(headers) => take(get_pgn(c(state).as_ptr(), c(headers).as_ptr())),
+        None => take(get_pgn(c(state).as_ptr(), std::ptr::null())),
+    }
+}
+
+#[test]
+fn pgn_export_records_the_result_and_how_the_game_ended() {
+    let mated = pgn(&play_alone(&FOOLS_MATE), None);
+    assert!(mated.starts_with("[Event \"LLM Arena Match\"]\n"), "{}", mated);
+    assert!(mated.contains("[White \"player1\"]\n[Black \"player2\"]\n[Result \"0-1\"]\n"), "{}", mated);
+    assert!(mated.contains("[Termination \"normal\"]"), "{}", mated);
+    assert!(mated.ends_with("\n\n1. f3 e5 2. g4 Qh4# {Checkmate} 0-1\n"), "{}", mated);
+
+    let drawn = pgn(&play_alone(&REPETITION), None);
+    assert!(drawn.contains("[Result \"1/2-1/2\"]"), "{}", drawn);
+    assert!(drawn.ends_with("{Draw by threefold repetition}\n1/2-1/2\n"), "{}", drawn);
+
+    let ongoing = pgn(&play_alone(&["e2e4"]), None);
+    assert!(ongoing.contains("[Result \"*\"]\n[Termination \"unterminated\"]"), "{}", ongoing);
+    assert!(ongoing.ends_with("\n\n1. e4 *\n"), "{}", ongoing);
+}
+
+#[test]
+fn pgn_headers_fill_the_roster_but_not_the_result() {
+    let headers = r#"{"White": "model-a", "Round": 3, "Result": "1-0", "Annotator": "arena"}"#;
+    let exported = pgn(&play_alone(&FOOLS_MATE), Some(headers));
+    let tags = "[Round \"3\"]\n[White \"model-a\"]\n[Black \"player2\"]\n[Result \"0-1\"]\n[Annotator \"arena\"]";
+    assert!(exported.contains(tags), "{}", exported);
+
+    assert_eq!(pgn(&play_alone(&FOOLS_MATE), Some("not json")), pgn(&play_alone(&FOOLS_MATE), None));
+}
+
+#[test]
+fn pgn_export_of_an_imported_position_sets_it_up() {
+    let state = import("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3");
+    let exported = pgn(&play(apply_move, &state, "d4e3"), None);
+    let tags = "[SetUp \"1\"]\n[FEN \"rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3\"]";
+    assert!(exported.contains(tags), "{}", exported);
+    assert!(exported.ends_with("\n\n3... dxe3 *\n"), "{}", exported);
+
+    assert!(!pgn(&play_alone(&["e2e4"]), None).contains("SetUp"));
+}
+
+#[test]
+fn pgn_import_replays_the_main_line() {
+    let text = "[Event \"Casual\"]\n\n1. e4 {best by test} e5 2. Nf3 (2. f4) Nc6 $1 3. Bb5 a6 *\n";
+    let state = import(text);
+    assert_eq!(fen(&state), fen(&play_alone(&["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6"])));
+    assert!(pgn(&state, None).ends_with("\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 *\n"));
+
+    let from_setup = import("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K3 b - - 5 40\"]\n\n40... Kd7 41. Ra7+ *");
+    assert_eq!(fen(&from_setup), "8/R2k4/8/8/8/8/8/4K3 b - - 7 41");
+}
+
+#[test]
+fn unreadable_positions_are_invalid() {
+    for (position, message) in [
+        ("", "empty position"),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", "invalid FEN"),
+        ("1. e4 e5 2. Ke3", "move 'Ke3' at ply 3"),
+        ("1. e4 (1. d4", "unterminated variation in PGN"),
+    ] {
+        let result = take(get_initial_state_from(c(position).as_ptr()));
+        assert!(result.starts_with("ERROR:"), "{}", result);
+        assert!(result.contains("\"code\":\"invalid_position\""), "{}", result);
+        assert!(result.contains(message), "{} not in {}", message, result);
+    }
+}
+
+// Evaluation
+
+#[test]
+fn evaluate_reports_the_score_and_its_terms() {
+    let level: serde_json::Value = serde_json::from_str(&take(evaluate(c(&take(get_initial_state())).as_ptr()))).unwrap();
+    for term in ["score", "material", "placement", "mobility", "king_safety", "pawn_structure"] {
+        assert_eq!(level[term], 0, "{}", term);
+    }
+
+    let state = import("rnb1kbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3");
+    let ahead: serde_json::Value = serde_json::from_str(&take(evaluate(c(&state).as_ptr()))).unwrap();
+    assert_eq!(ahead["material"], 900);
+    assert!(ahead["score"].as_i64().unwrap() > 0);
+}
+
+#[test]
+fn analyze_game_grades_every_move_of_the_game() {
+    let report: serde_json::Value =
+        serde_json::from_str(&take(analyze_game(c(&play_alone(&FOOLS_MATE)).as_ptr(), 2))).unwrap();
+    let moves = report["moves"].as_array().unwrap();
+    assert_eq!(moves.len(), 4);
+    assert_eq!(moves[2]["san"], "g4");
+    assert_eq!(moves[2]["classification"], "blunder");
+    assert_eq!(moves[3]["san"], "Qh4#");
+    assert_eq!(moves[3]["cp_loss"], 0);
+    assert_eq!(report["player1"]["blunders"], 1);
+    assert_eq!(report["player2"]["moves"], 2);
+}
+
+// Capabilities
+
+#[test]
+fn capabilities_advertise_the_chess_extras() {
+    let capabilities: serde_json::Value = serde_json::from_str(&take(get_capabilities())).unwrap();
+    for extra in ["notation", "hints", "analysis", "draw_reasons", "custom_start"] {
+        assert_eq!(capabilities[extra], true, "{}", extra);
+    }
+    assert_eq!(capabilities["hidden_info"], false);
+    assert_eq!(capabilities["multiplayer"], false);
+    assert_eq!(capabilities["players"], 2);
+}
+
+// Binary states
+
+#[cfg(feature = "binary-state")]
+fn round_trip(state: &str) -> String {
+    let buffer = encode_state(c(state).as_ptr());
+    assert!(!buffer.is_null(), "{}", take(get_last_error()));
+    let bytes = unsafe {
+        let len = u32::from_le_bytes(std::slice::from_raw_parts(buffer, 4).try_into().unwrap());
+        std::slice::from_raw_parts(buffer.add(4), len as usize).to_vec()
+    };
+    arena_free(buffer);
+    take(decode_state(bytes.as_ptr(), bytes.len()))
+}
+
+#[cfg(feature = "binary-state")]
+#[test]
+fn binary_states_keep_every_field() {
+    let played = play_alone(&SCHOLARS_MATE[..4]);
+    assert_eq!(round_trip(&played), played);
+
+    let imported = play(apply_move, &import("8/8/8/4k3/8/8/R7/4K3 b - - 37 61"), "e5d5");
+    assert_eq!(round_trip(&imported), imported);
+}
+
+// Interleaved games
+
+const FOOLS_MATE: [&str; 4] = ["f2f3", "e7e5", "g2g4", "d8h4"];
+const SCHOLARS_MATE: [&str; 7] = ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"];
+const REPETITION: [&str; 8] = ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"];
+
+fn play_alone(moves: &[&str]) -> String {
+    moves.iter().fold(take(get_initial_state()), |state, move_str| play(apply_move, &state, move_str))
+}
+
+#[test]
+fn interleaved_games_keep_their_own_positions() {
+    let games: [&[&str]; 3] = [&FOOLS_MATE, &SCHOLARS_MATE, &REPETITION];
+    let mut states: Vec<String> = games.iter().map(|_| take(get_initial_state())).collect();
+
+    for ply in 0..SCHOLARS_MATE.len().max(REPETITION.len()) {
+        for (state, moves) in states.iter_mut().zip(games) {
+            if let Some(move_str) = moves.get(ply) {
+                *state = play(apply_move, state, move_str);
+            }
+        }
+    }
+
+    assert_eq!(winner(&states[0]), "player2");
+    assert_eq!(winner(&states[1]), "player1");
+    assert_eq!(winner(&states[2]), "draw");
+    assert_eq!(take(get_draw_reason(c(&states[2]).as_ptr())), "threefold_repetition");
+    for (state, moves) in states.iter().zip(games) {
+        assert_eq!(fen(state), fen(&play_alone(moves)));
+    }
+}
+
+#[test]
+fn starting_a_new_game_leaves_running_games_alone() {
+    let mut state = take(get_initial_state());
+    for move_str in &SCHOLARS_MATE[..4] {
+        state = play(apply_move, &state, move_str);
+        let fresh = take(get_initial_state());
+        assert_eq!(take(get_current_player(c(&fresh).as_ptr())), "player1");
+    }
+
+    let moves: Vec<String> = serde_json::from_str(&take(get_valid_moves(c(&state).as_ptr()))).unwrap();
+    assert!(moves.contains(&"d1h5".to_string()));
+    assert_eq!(take(get_current_player(c(&state).as_ptr())), "player1");
+}
+
+#[test]
+fn interleaved_handles_keep_their_own_positions() {
+    let fools = handle_create();
+    let scholars = handle_create();
+
+    for (ply, move_str) in SCHOLARS_MATE.iter().enumerate() {
+        if let Some(move_str) = FOOLS_MATE.get(ply) {
+            assert_eq!(handle_apply_move(fools, c(move_str).as_ptr()), 0);
+        }
+        assert_eq!(handle_apply_move(scholars, c(move_str).as_ptr()), 0);
+    }
+
+    assert_eq!(take(handle_get_winner(fools)), "player2");
+    assert_eq!(take(handle_get_winner(scholars)), "player1");
+    assert_eq!(fen(&take(handle_get_state(scholars))), fen(&play_alone(&SCHOLARS_MATE)));
+
+    handle_drop(fools);
+    assert_eq!(handle_is_game_over(fools), -1);
+    assert_eq!(handle_is_game_over(scholars), 1);
+    handle_drop(scholars);
+}
+
+#[test]
+fn a_handle_whose_moves_do_not_reach_its_fen_answers_like_the_json() {
+    // The move list claims e4 was played, but the FEN is still the start.
+    let mut state: serde_json::Value = serde_json::from_str(&take(get_initial_state())).unwrap();
+    state["moves"] = serde_json::json!(["e2e4"]);
+    let mut state = state.to_string();
+    let handle = handle_from_state(c(&state).as_ptr());
+
+    for move_str in REPETITION {
+        state = play(apply_move, &state, move_str);
+        assert_eq!(handle_apply_move(handle, c(move_str).as_ptr()), 0);
+        assert_eq!(handle_is_game_over(handle), is_game_over(c(&state).as_ptr()));
+        assert_eq!(take(handle_get_turn_info(handle)), take(get_turn_info(c(&state).as_ptr())));
+    }
+
+    let from_handle = take(handle_get_state(handle));
+    assert_eq!(from_handle, state);
+    assert_eq!(pgn(&from_handle, None), pgn(&state, None));
+    assert_eq!(draw_reason(&from_handle), draw_reason(&state));
+    handle_drop(handle);
+}
+
+#[test]
+fn games_on_separate_threads_do_not_interfere() {
+    let games: [&'static [&'static str]; 3] = [&FOOLS_MATE, &SCHOLARS_MATE, &REPETITION];
+    let workers: Vec<_> = games
+        .iter()
+        .map(|&moves| thread::spawn(move || (0..20).map(|_| winner(&play_alone(moves))).collect::<Vec<_>>()))
+        .collect();
+
+    let results: Vec<Vec<String>> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+    for (results, expected) in results.iter().zip(["player2", "player1", "draw"]) {
+        assert!(results.iter().all(|result| result == expected), "{:?}", results);
+    }
+}
+
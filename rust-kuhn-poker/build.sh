@@ -0,0 +1,25 @@
+#!/bin/bash
+
+# Build script for Kuhn poker WASM game
+
+echo "🦀 Building Kuhn Poker WASM..."
+
+# Check if wasm-pack is installed
+if ! command -v wasm-pack &> /dev/null; then
+    echo "❌ wasm-pack is not installed"
+    echo "Install with: curl https://rustwasm.github.io/wasm-pack/installer/init.sh -sSf | sh"
+    exit 1
+fi
+
+# Build the WASM module; the arena provides env.host_log for its transcript,
+# and binary-state adds MessagePack states for bulk simulation
+wasm-pack build --target web --out-dir pkg -- --features host-log,binary-state
+
+# Copy files to public directory if it exists
+if [ -d "../public" ]; then
+    cp pkg/kuhn_poker_wasm.wasm ../public/
+    cp metadata.json ../public/kuhn_poker_metadata.json
+    echo "✅ Copied files to public directory"
+fi
+
+echo "✅ Kuhn Poker WASM build complete"
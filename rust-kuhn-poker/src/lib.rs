@@ -0,0 +1,686 @@
+use serde::{Deserialize, Serialize};
+use arena_game_sdk::ffi::{from_c_string, to_c_string, write_state};
+use arena_game_sdk::guard::guard;
+use arena_game_sdk::{Capabilities, GameError, Outcome};
+use std::os::raw::c_char;
+
+/// Hands in a match started with `get_initial_state`.
+const DEFAULT_HANDS: u32 = 10;
+
+/// Most hands a config may ask for.
+const MAX_HANDS: u32 = 1000;
+
+/// Chips each player puts in before the cards are dealt, and the size of
+/// the only bet.
+const ANTE: i32 = 1;
+const BET: i32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd, Debug)]
+enum Card {
+    J,
+    Q,
+    K,
+}
+
+const DECK: [Card; 3] = [Card::J, Card::Q, Card::K];
+
+impl Card {
+    fn name(self) -> &'static str {
+        match self {
+            Card::J => "J",
+            Card::Q => "Q",
+            Card::K => "K",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    Check,
+    Bet,
+    Call,
+    Fold,
+}
+
+impl Action {
+    fn parse(move_str: &str) -> Option<Action> {
+        match move_str.trim().to_lowercase().as_str() {
+            "check" => Some(Action::Check),
+            "bet" => Some(Action::Bet),
+            "call" => Some(Action::Call),
+            "fold" => Some(Action::Fold),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Action::Check => "check",
+            Action::Bet => "bet",
+            Action::Call => "call",
+            Action::Fold => "fold",
+        }
+    }
+
+    /// "checks", "bets", ... for the rendered action list.
+    fn verb(self) -> &'static str {
+        match self {
+            Action::Check => "checks",
+            Action::Bet => "bets",
+            Action::Call => "calls",
+            Action::Fold => "folds",
+        }
+    }
+}
+
+/// A match of `hands` hands of Kuhn poker. The cards of every hand follow
+/// from `seed`, so the state has to stay with the host: agents are shown
+/// `get_observation` or `render_for`, never the state itself, and nothing
+/// else the module returns mentions the seed.
+#[derive(Serialize, Deserialize, Clone)]
+struct GameState {
+    seed: u32,
+    hands: u32,
+    /// The actions of every finished hand, in order.
+    played: Vec<Vec<Action>>,
+    /// The actions so far in the hand being played.
+    actions: Vec<Action>,
+}
+
+/// Match settings for `get_initial_state_with_config`.
+#[derive(Deserialize)]
+struct MatchConfig {
+    #[serde(default)]
+    seed: u32,
+    #[serde(default = "default_hands")]
+    hands: u32,
+}
+
+fn default_hands() -> u32 {
+    DEFAULT_HANDS
+}
+
+impl MatchConfig {
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_HANDS).contains(&self.hands) {
+            return Err(format!("hands must be between 1 and {}", MAX_HANDS));
+        }
+        Ok(())
+    }
+
+    fn initial_state(&self) -> GameState {
+        GameState {
+            seed: self.seed,
+            hands: self.hands,
+            played: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// The cards dealt to player1 and player2 in hand `hand` of a match
+/// started with `seed`: two different cards of the three, picked by a
+/// splitmix64 step so every hand of every seed is dealt independently.
+fn deal(seed: u32, hand: usize) -> [Card; 2] {
+    let mut x = ((seed as u64) << 32 | hand as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+
+    let first = (x % 3) as usize;
+    let second = (first + 1 + ((x >> 32) % 2) as usize) % 3;
+    [DECK[first], DECK[second]]
+}
+
+fn player_name(seat: usize) -> String {
+    format!("player{}", seat + 1)
+}
+
+fn seat_of(player: &str) -> usize {
+    if player == "player2" {
+        1
+    } else {
+        0
+    }
+}
+
+/// The seat acting first in hand `hand`; the players take turns.
+fn first_to_act(hand: usize) -> usize {
+    hand % 2
+}
+
+/// What may be played after `actions`, empty once the hand is decided.
+fn legal_actions(actions: &[Action]) -> &'static [Action] {
+    match actions {
+        [] | [Action::Check] => &[Action::Check, Action::Bet],
+        [Action::Bet] | [Action::Check, Action::Bet] => &[Action::Fold, Action::Call],
+        _ => &[],
+    }
+}
+
+/// Whether every action in `actions` was allowed when it was played.
+fn is_sequence(actions: &[Action]) -> bool {
+    actions.iter().enumerate().all(|(index, action)| legal_actions(&actions[..index]).contains(action))
+}
+
+/// How a finished hand went.
+struct Settlement {
+    winner: usize,
+    /// Chips the winner took from the loser.
+    amount: i32,
+    /// Whether the cards were shown; after a fold they stay hidden.
+    showdown: bool,
+}
+
+/// Settles a hand once `actions` end it: a fold gives the pot to the
+/// other player, and two checks or a call go to showdown.
+fn settle(cards: [Card; 2], first: usize, actions: &[Action]) -> Option<Settlement> {
+    if !legal_actions(actions).is_empty() {
+        return None;
+    }
+
+    let mut put_in = [ANTE; 2];
+    let mut folded = None;
+    for (index, action) in actions.iter().enumerate() {
+        let seat = (first + index) % 2;
+        match action {
+            Action::Bet | Action::Call => put_in[seat] += BET,
+            Action::Fold => folded = Some(seat),
+            Action::Check => {}
+        }
+    }
+
+    let (winner, showdown) = match folded {
+        Some(seat) => (1 - seat, false),
+        None => (if cards[0] > cards[1] { 0 } else { 1 }, true),
+    };
+    Some(Settlement {
+        winner,
+        amount: put_in[1 - winner],
+        showdown,
+    })
+}
+
+impl GameState {
+    fn hand(&self) -> usize {
+        self.played.len()
+    }
+
+    fn is_over(&self) -> bool {
+        self.hand() >= self.hands as usize
+    }
+
+    fn cards(&self) -> [Card; 2] {
+        deal(self.seed, self.hand())
+    }
+
+    fn to_act(&self) -> usize {
+        (first_to_act(self.hand()) + self.actions.len()) % 2
+    }
+
+    fn pot(&self) -> i32 {
+        let bets = self.actions.iter().filter(|action| matches!(action, Action::Bet | Action::Call)).count();
+        2 * ANTE + bets as i32 * BET
+    }
+
+    /// Every finished hand with its settlement. `check_state` makes sure
+    /// every hand in `played` was finished.
+    fn results(&self) -> impl Iterator<Item = (usize, [Card; 2], &Vec<Action>, Settlement)> {
+        self.played.iter().enumerate().filter_map(move |(hand, actions)| {
+            let cards = deal(self.seed, hand);
+            settle(cards, first_to_act(hand), actions).map(|settlement| (hand, cards, actions, settlement))
+        })
+    }
+
+    /// Net chips won by each player over the finished hands.
+    fn chips(&self) -> [i32; 2] {
+        let mut chips = [0; 2];
+        for (_, _, _, settlement) in self.results() {
+            chips[settlement.winner] += settlement.amount;
+            chips[1 - settlement.winner] -= settlement.amount;
+        }
+        chips
+    }
+
+    /// The actions of a hand as "player1 checks, player2 bets".
+    fn describe_actions(hand: usize, actions: &[Action]) -> String {
+        let described: Vec<String> = actions
+            .iter()
+            .enumerate()
+            .map(|(index, action)| format!("{} {}", player_name((first_to_act(hand) + index) % 2), action.verb()))
+            .collect();
+        if described.is_empty() {
+            "none yet".to_string()
+        } else {
+            described.join(", ")
+        }
+    }
+
+    /// The table as seen from `viewer`'s seat, or by a spectator who sees
+    /// both cards when `viewer` is None.
+    fn describe(&self, viewer: Option<usize>) -> String {
+        let chips = self.chips();
+        let mut output = String::new();
+
+        if let Some(seat) = viewer {
+            output.push_str(&format!("You are {}\n", player_name(seat)));
+        }
+        output.push_str(&format!("Chips: player1 {:+}, player2 {:+}\n", chips[0], chips[1]));
+
+        if self.is_over() {
+            output.push_str(&format!("Match over after {} hands\n", self.hands));
+        } else {
+            let cards = self.cards();
+            let shown = |seat: usize| match viewer {
+                Some(viewer) if viewer != seat => "?",
+                _ => cards[seat].name(),
+            };
+            output.push_str(&format!("\nHand {} of {}\n", self.hand() + 1, self.hands));
+            output.push_str(&format!("player1: {}   player2: {}\n", shown(0), shown(1)));
+            output.push_str(&format!("Pot: {}\n", self.pot()));
+            output.push_str(&format!("Actions: {}\n", Self::describe_actions(self.hand(), &self.actions)));
+            output.push_str(&format!("To act: {}\n", player_name(self.to_act())));
+        }
+
+        if let Some((hand, cards, actions, settlement)) = self.results().last() {
+            let shown = |seat: usize| match viewer {
+                Some(viewer) if viewer != seat && !settlement.showdown => "?",
+                _ => cards[seat].name(),
+            };
+            output.push_str(&format!(
+                "\nLast hand: player1 {} vs player2 {}; {}; {} won {}\n",
+                shown(0),
+                shown(1),
+                Self::describe_actions(hand, actions),
+                player_name(settlement.winner),
+                settlement.amount
+            ));
+        }
+        output
+    }
+}
+
+/// Why `apply_move` refused a move, reported to the host as
+/// `ERROR: {"code": ..., "message": ...}` so the agent can be told.
+enum MoveError {
+    Malformed(String),
+    NotAllowed(Action, &'static [Action]),
+    GameOver,
+}
+
+impl MoveError {
+    fn code(&self) -> &'static str {
+        match self {
+            MoveError::Malformed(_) => "malformed",
+            MoveError::NotAllowed(..) => "not_allowed",
+            MoveError::GameOver => "game_over",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            MoveError::Malformed(input) => {
+                format!("'{}' is not a move; use check, bet, call or fold", input)
+            }
+            MoveError::NotAllowed(action, allowed) => {
+                let allowed: Vec<&str> = allowed.iter().map(|action| action.name()).collect();
+                format!("you cannot {} now; choose {}", action.name(), allowed.join(" or "))
+            }
+            MoveError::GameOver => "the match is already over".to_string(),
+        }
+    }
+}
+
+impl From<MoveError> for GameError {
+    fn from(error: MoveError) -> GameError {
+        GameError::new(error.code(), error.message())
+    }
+}
+
+struct KuhnPoker;
+
+impl arena_game_sdk::Game for KuhnPoker {
+    type State = GameState;
+
+    const NAME: &'static str = "Kuhn Poker";
+    const DESCRIPTION: &'static str =
+        "Kuhn poker: a three-card deck, one card each and a single bet, played over a match of several hands";
+    const CAPABILITIES: Capabilities = Capabilities {
+        custom_start: true,
+        hidden_info: true,
+        ..Capabilities::BASIC
+    };
+
+    /// Always dealt from seed 0, so every match started this way gets the
+    /// same cards, which suits reproducible benchmarks. The arena starts
+    /// matches through `get_initial_state_with_config` with a seed of its
+    /// own instead.
+    fn initial_state() -> GameState {
+        MatchConfig {
+            seed: 0,
+            hands: DEFAULT_HANDS,
+        }
+        .initial_state()
+    }
+
+    fn check_state(state: &GameState) -> Result<(), String> {
+        MatchConfig {
+            seed: state.seed,
+            hands: state.hands,
+        }
+        .validate()?;
+        if state.played.len() > state.hands as usize {
+            return Err(format!("played holds more than the match's {} hands", state.hands));
+        }
+        if let Some(hand) = state
+            .played
+            .iter()
+            .position(|actions| !is_sequence(actions) || !legal_actions(actions).is_empty())
+        {
+            return Err(format!("hand {} in played is not a finished hand", hand + 1));
+        }
+        if !is_sequence(&state.actions) || legal_actions(&state.actions).is_empty() {
+            return Err("actions is not a hand in progress".to_string());
+        }
+        if state.is_over() && !state.actions.is_empty() {
+            return Err("actions must be empty once the match is over".to_string());
+        }
+        Ok(())
+    }
+
+    fn legal_moves(state: &GameState) -> Vec<String> {
+        if state.is_over() {
+            return Vec::new();
+        }
+        legal_actions(&state.actions).iter().map(|action| action.name().to_string()).collect()
+    }
+
+    fn apply(state: &mut GameState, move_str: &str) -> Result<(), GameError> {
+        if state.is_over() {
+            return Err(MoveError::GameOver.into());
+        }
+        let action = Action::parse(move_str).ok_or_else(|| MoveError::Malformed(move_str.to_string()))?;
+        let allowed = legal_actions(&state.actions);
+        if !allowed.contains(&action) {
+            return Err(MoveError::NotAllowed(action, allowed).into());
+        }
+
+        state.actions.push(action);
+        if legal_actions(&state.actions).is_empty() {
+            let actions = std::mem::take(&mut state.actions);
+            state.played.push(actions);
+        }
+        Ok(())
+    }
+
+    fn outcome(state: &GameState) -> Outcome {
+        if !state.is_over() {
+            return Outcome::Ongoing;
+        }
+        let chips = state.chips();
+        if chips[0] > chips[1] {
+            Outcome::Winner("player1".to_string())
+        } else if chips[1] > chips[0] {
+            Outcome::Winner("player2".to_string())
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    fn current_player(state: &GameState) -> String {
+        player_name(state.to_act())
+    }
+
+    fn render(state: &GameState) -> String {
+        state.describe(None)
+    }
+
+    /// The player's own card, the public actions and chip counts, and the
+    /// opponent's card only for hands that went to showdown.
+    fn observation(state: &GameState, player: &str) -> serde_json::Value {
+        let seat = seat_of(player);
+        let chips = state.chips();
+        let history: Vec<serde_json::Value> = state
+            .results()
+            .map(|(hand, cards, actions, settlement)| {
+                serde_json::json!({
+                    "hand": hand + 1,
+                    "first_to_act": player_name(first_to_act(hand)),
+                    "actions": actions,
+                    "card": cards[seat],
+                    "opponent_card": if settlement.showdown { Some(cards[1 - seat]) } else { None },
+                    "winner": player_name(settlement.winner),
+                    "amount": settlement.amount,
+                })
+            })
+            .collect();
+
+        let mut observation = serde_json::json!({
+            "player": player,
+            "hands": state.hands,
+            "chips": { "player1": chips[0], "player2": chips[1] },
+            "history": history,
+        });
+        if !state.is_over() {
+            observation["hand"] = (state.hand() + 1).into();
+            observation["card"] = serde_json::to_value(state.cards()[seat]).unwrap_or_default();
+            observation["first_to_act"] = player_name(first_to_act(state.hand())).into();
+            observation["actions"] = serde_json::to_value(&state.actions).unwrap_or_default();
+            observation["pot"] = state.pot().into();
+            observation["to_act"] = player_name(state.to_act()).into();
+        }
+        observation
+    }
+
+    fn render_for(state: &GameState, player: &str) -> String {
+        state.describe(Some(seat_of(player)))
+    }
+
+    fn transcript(state: &GameState) -> String {
+        let mut transcript = String::new();
+        transcript.push_str("=== KUHN POKER TRANSCRIPT ===\n");
+        transcript.push_str(&format!("Hands: {}\n", state.hands));
+
+        for (hand, cards, actions, settlement) in state.results() {
+            transcript.push_str(&format!(
+                "Hand {}: player1 {} vs player2 {}; {}; {} won {}{}\n",
+                hand + 1,
+                cards[0].name(),
+                cards[1].name(),
+                GameState::describe_actions(hand, actions),
+                player_name(settlement.winner),
+                settlement.amount,
+                if settlement.showdown { " at showdown" } else { "" }
+            ));
+        }
+
+        let chips = state.chips();
+        transcript.push_str(&format!("Chips: player1 {:+}, player2 {:+}\n", chips[0], chips[1]));
+        transcript.push_str(&format!("Winner: {}\n", Self::outcome(state).winner()));
+        transcript.push_str("=============================\n");
+        transcript
+    }
+}
+
+arena_game_sdk::export_game!(KuhnPoker);
+arena_game_sdk::embed_metadata!("../metadata.json");
+
+/// Starts a match with its own deal, e.g. `{"seed": 42, "hands": 20}`.
+/// `seed` defaults to 0 and `hands` to 10; a bad config returns an
+/// `invalid_config` error result. The module has no source of randomness,
+/// so the host picks a fresh seed for each match.
+#[no_mangle]
+pub extern "C" fn get_initial_state_with_config(config_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let config_str = from_c_string(config_ptr);
+        let config: MatchConfig = match serde_json::from_str(&config_str) {
+            Ok(config) => config,
+            Err(error) => {
+                let message = format!("config must be a JSON object with seed and hands: {}", error);
+                return to_c_string(GameError::new("invalid_config", message).to_result());
+            }
+        };
+        if let Err(message) = config.validate() {
+            return to_c_string(GameError::new("invalid_config", message).to_result());
+        }
+
+        write_state::<KuhnPoker>(&config.initial_state())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use arena_game_sdk::ffi::test_util::{c, take};
+    use arena_game_sdk::Game;
+
+    use super::*;
+    use Action::{Bet, Call, Check, Fold};
+
+    fn started(seed: u32, hands: u32) -> GameState {
+        MatchConfig { seed, hands }.initial_state()
+    }
+
+    fn play(state: &mut GameState, moves: &[&str]) {
+        for move_str in moves {
+            KuhnPoker::apply(state, move_str).unwrap();
+        }
+    }
+
+    /// A seed whose first hand deals `cards`.
+    fn seed_dealing(cards: [Card; 2]) -> u32 {
+        (0..).find(|&seed| deal(seed, 0) == cards).unwrap()
+    }
+
+    #[test]
+    fn every_hand_deals_two_different_cards() {
+        for seed in [0, 1, 42, u32::MAX] {
+            for hand in 0..50 {
+                let [first, second] = deal(seed, hand);
+                assert_ne!(first, second);
+                assert_eq!(deal(seed, hand), [first, second]);
+            }
+        }
+        let deals = |seed| (0..20).map(|hand| deal(seed, hand)).collect::<Vec<_>>();
+        assert_ne!(deals(1), deals(2));
+    }
+
+    #[test]
+    fn a_fold_costs_the_ante_and_bets_raise_the_stakes() {
+        let cards = [Card::K, Card::J];
+        let settled = |first, actions: &[Action]| {
+            let settlement = settle(cards, first, actions).unwrap();
+            (settlement.winner, settlement.amount, settlement.showdown)
+        };
+
+        assert_eq!(settled(0, &[Check, Check]), (0, 1, true));
+        assert_eq!(settled(0, &[Bet, Call]), (0, 2, true));
+        assert_eq!(settled(1, &[Check, Bet, Call]), (0, 2, true));
+        assert_eq!(settled(0, &[Bet, Fold]), (0, 1, false));
+        assert_eq!(settled(1, &[Bet, Fold]), (1, 1, false));
+        assert_eq!(settled(0, &[Check, Bet, Fold]), (1, 1, false));
+        assert!(settle(cards, 0, &[Check, Bet]).is_none());
+    }
+
+    #[test]
+    fn players_take_turns_acting_first_and_the_chips_decide_the_match() {
+        let mut state = started(7, 2);
+        assert_eq!(KuhnPoker::current_player(&state), "player1");
+        play(&mut state, &["bet", "fold"]);
+        assert_eq!(KuhnPoker::current_player(&state), "player2");
+        assert_eq!(state.chips(), [1, -1]);
+
+        play(&mut state, &["check", "bet", "fold"]);
+        assert_eq!(state.chips(), [2, -2]);
+        assert_eq!(KuhnPoker::outcome(&state), Outcome::Winner("player1".to_string()));
+        assert!(KuhnPoker::legal_moves(&state).is_empty());
+        assert_eq!(KuhnPoker::apply(&mut state, "check").unwrap_err().code, "game_over");
+
+        let mut even = started(7, 2);
+        play(&mut even, &["bet", "fold", "bet", "fold"]);
+        assert_eq!(KuhnPoker::outcome(&even), Outcome::Draw);
+    }
+
+    #[test]
+    fn only_allowed_actions_are_accepted() {
+        let mut state = started(0, 1);
+        let error = KuhnPoker::apply(&mut state, "call").unwrap_err();
+        assert_eq!(error, GameError::new("not_allowed", "you cannot call now; choose check or bet"));
+        assert_eq!(KuhnPoker::apply(&mut state, "raise").unwrap_err().code, "malformed");
+        play(&mut state, &[" BET "]);
+        assert_eq!(KuhnPoker::legal_moves(&state), ["fold", "call"]);
+    }
+
+    #[test]
+    fn players_see_their_own_card_and_the_other_only_at_showdown() {
+        let mut state = started(seed_dealing([Card::Q, Card::K]), 3);
+        for (player, own, hidden) in [("player1", "\"Q\"", "\"K\""), ("player2", "\"K\"", "\"Q\"")] {
+            let observation = KuhnPoker::observation(&state, player);
+            assert_eq!(observation["card"].to_string(), own);
+            assert!(!observation.to_string().contains(hidden), "{}", observation);
+        }
+        assert!(KuhnPoker::render_for(&state, "player1").contains("player1: Q   player2: ?"));
+        assert!(KuhnPoker::render_for(&state, "player2").contains("player1: ?   player2: K"));
+
+        play(&mut state, &["bet", "call"]);
+        let history = &KuhnPoker::observation(&state, "player1")["history"][0];
+        assert_eq!(history["card"], "Q");
+        assert_eq!(history["opponent_card"], "K");
+        assert_eq!(history["winner"], "player2");
+        assert_eq!(history["amount"], 2);
+    }
+
+    #[test]
+    fn turn_info_shows_only_the_card_of_the_player_to_act() {
+        let mut state = started(seed_dealing([Card::Q, Card::K]), 3);
+        for (to_act, seen) in [("player1", "player1: Q   player2: ?"), ("player2", "player1: ?   player2: K")] {
+            let json = serde_json::to_string(&state).unwrap();
+            let info: serde_json::Value = serde_json::from_str(&take(get_turn_info(c(&json).as_ptr()))).unwrap();
+            assert_eq!(info["current_player"], to_act);
+            let render = info["render"].as_str().unwrap();
+            assert!(render.contains(seen), "{}", render);
+
+            let handle = handle_from_state(c(&json).as_ptr());
+            assert_eq!(take(handle_get_turn_info(handle)), info.to_string());
+            handle_drop(handle);
+            play(&mut state, &["check"]);
+        }
+    }
+
+    #[test]
+    fn a_folded_hand_stays_hidden() {
+        let mut state = started(seed_dealing([Card::J, Card::K]), 3);
+        play(&mut state, &["bet", "fold"]);
+
+        let history = &KuhnPoker::observation(&state, "player2")["history"][0];
+        assert_eq!(history["card"], "K");
+        assert_eq!(history["opponent_card"], serde_json::Value::Null);
+        assert!(KuhnPoker::render_for(&state, "player2").contains("Last hand: player1 ? vs player2 K"));
+        assert!(KuhnPoker::render(&state).contains("Last hand: player1 J vs player2 K"));
+    }
+
+    #[test]
+    fn observations_are_only_for_seated_players() {
+        let state = serde_json::to_string(&started(0, 1)).unwrap();
+        for player in ["player3", "player0", "spectator", "player01", ""] {
+            let result = take(get_observation(c(&state).as_ptr(), c(player).as_ptr()));
+            assert!(result.starts_with(r#"ERROR: {"code":"invalid_player""#), "{}: {}", player, result);
+        }
+        let observation = take(get_observation(c(&state).as_ptr(), c("player2").as_ptr()));
+        assert!(observation.contains(r#""player":"player2""#), "{}", observation);
+    }
+
+    #[test]
+    fn the_seed_appears_only_in_the_state() {
+        let seed = 3_141_592_653;
+        let mut state = started(seed, 3);
+        play(&mut state, &["bet", "fold"]);
+
+        for player in ["player1", "player2"] {
+            assert!(!KuhnPoker::observation(&state, player).to_string().contains(&seed.to_string()));
+            assert!(!KuhnPoker::render_for(&state, player).contains(&seed.to_string()));
+        }
+        assert!(!KuhnPoker::render(&state).contains(&seed.to_string()));
+        assert!(!KuhnPoker::transcript(&state).contains(&seed.to_string()));
+    }
+}
@@ -11,8 +11,9 @@ if ! command -v wasm-pack &> /dev/null; then
     exit 1
 fi
 
-# Build the WASM module
-wasm-pack build --target web --out-dir pkg
+# Build the WASM module; the arena provides env.host_log for its transcript,
+# and binary-state adds MessagePack states for bulk simulation
+wasm-pack build --target web --out-dir pkg -- --features host-log,binary-state
 
 # Copy files to public directory if it exists
 if [ -d "../public" ]; then
@@ -1,295 +1,879 @@
 use serde::{Deserialize, Serialize};
-use std::ffi::{CStr, CString};
+use arena_game_sdk::ffi::{from_c_string, read_state, to_c_string, write_state};
+use arena_game_sdk::guard::guard;
+use arena_game_sdk::{Capabilities, GameError, Outcome};
 use std::os::raw::c_char;
 
+mod solver;
+mod ultimate;
+
+use solver::Solver;
+
+/// Largest board side accepted from a config. Gomoku is played on 15x15
+/// or 19x19 boards, so this leaves room for larger m,n,k-games.
+const MAX_SIZE: usize = 26;
+
+fn default_size() -> usize {
+    3
+}
+
+/// Which rules the board is played under. Ultimate tic-tac-toe is a 9x9
+/// board of nine 3x3 sub-boards with the "send" rule.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+enum Variant {
+    #[default]
+    Standard,
+    Ultimate,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 struct GameState {
-    board: [[i32; 3]; 3],
+    board: Vec<Vec<i32>>,
     current_player: String,
     move_count: u32,
     winner: String,
+    #[serde(default)]
+    moves: Vec<String>,
+    #[serde(default = "default_size")]
+    rows: usize,
+    #[serde(default = "default_size")]
+    cols: usize,
+    #[serde(default = "default_size")]
+    win_length: usize,
+    #[serde(default)]
+    variant: Variant,
+    /// Ultimate only: the sub-board the player to move is sent to, or
+    /// `None` when any open sub-board may be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_board: Option<usize>,
 }
 
-fn to_c_string(s: String) -> *mut c_char {
-    CString::new(s).unwrap().into_raw()
+/// Board shape for `get_initial_state_with_config`. Anything left out
+/// keeps the classic 3x3, three-in-a-row value; the ultimate variant
+/// always uses its own 9x9 board.
+#[derive(Deserialize)]
+struct BoardConfig {
+    #[serde(default)]
+    variant: Variant,
+    #[serde(default = "default_size")]
+    rows: usize,
+    #[serde(default = "default_size")]
+    cols: usize,
+    #[serde(default = "default_size")]
+    win_length: usize,
 }
 
-fn from_c_string(ptr: *const c_char) -> String {
-    unsafe {
-        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+impl BoardConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.variant == Variant::Ultimate {
+            return Ok(());
+        }
+        if !(1..=MAX_SIZE).contains(&self.rows) || !(1..=MAX_SIZE).contains(&self.cols) {
+            return Err(format!("rows and cols must be between 1 and {}", MAX_SIZE));
+        }
+        if self.win_length < 1 || self.win_length > self.rows.max(self.cols) {
+            return Err(format!(
+                "win_length must be between 1 and {} for a {}x{} board",
+                self.rows.max(self.cols),
+                self.rows,
+                self.cols
+            ));
+        }
+        Ok(())
     }
-}
-
-#[no_mangle]
-pub extern "C" fn get_initial_state() -> *mut c_char {
-    let state = GameState {
-        board: [[0; 3]; 3],
-        current_player: "player1".to_string(),
-        move_count: 0,
-        winner: "".to_string(),
-    };
-    
-    to_c_string(serde_json::to_string(&state).unwrap())
-}
 
-#[no_mangle]
-pub extern "C" fn get_valid_moves(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
+    fn initial_state(&self) -> GameState {
+        let (rows, cols, win_length) = match self.variant {
+            Variant::Standard => (self.rows, self.cols, self.win_length),
+            Variant::Ultimate => (ultimate::BOARD_SIZE, ultimate::BOARD_SIZE, ultimate::SIZE),
+        };
         GameState {
-            board: [[0; 3]; 3],
+            board: vec![vec![0; cols]; rows],
             current_player: "player1".to_string(),
             move_count: 0,
             winner: "".to_string(),
+            moves: Vec::new(),
+            rows,
+            cols,
+            win_length,
+            variant: self.variant,
+            active_board: None,
+        }
+    }
+}
+
+/// Why `apply_move` refused a move, reported to the host as
+/// `ERROR: {"code": ..., "message": ...}` so the agent can be told.
+enum MoveError {
+    Malformed(String),
+    /// Row and column as typed, so negative ones can be reported too.
+    OutOfBounds(i64, i64, usize, usize),
+    Occupied(usize, usize),
+    WrongBoard(usize),
+    BoardClosed(usize),
+    GameOver,
+}
+
+impl MoveError {
+    fn code(&self) -> &'static str {
+        match self {
+            MoveError::Malformed(_) => "malformed",
+            MoveError::OutOfBounds(..) => "out_of_bounds",
+            MoveError::Occupied(_, _) => "occupied",
+            MoveError::WrongBoard(_) => "wrong_board",
+            MoveError::BoardClosed(_) => "board_closed",
+            MoveError::GameOver => "game_over",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            MoveError::Malformed(input) => {
+                format!("'{}' is not a move; use \"row,col\" counting from 0, e.g. \"1,1\"", input)
+            }
+            MoveError::OutOfBounds(row, col, rows, cols) => format!(
+                "{},{} is off the board; rows go from 0 to {} and columns from 0 to {}",
+                row,
+                col,
+                rows - 1,
+                cols - 1
+            ),
+            MoveError::Occupied(row, col) => format!("cell {},{} is already taken", row, col),
+            MoveError::WrongBoard(index) => format!(
+                "the last move sends you to sub-board {} ({})",
+                index,
+                ultimate::describe(*index)
+            ),
+            MoveError::BoardClosed(index) => format!(
+                "sub-board {} ({}) is already decided; play in an open one",
+                index,
+                ultimate::describe(*index)
+            ),
+            MoveError::GameOver => "the game is already over".to_string(),
         }
-    });
-    
+    }
+
+}
+
+impl From<MoveError> for GameError {
+    fn from(error: MoveError) -> GameError {
+        GameError::new(error.code(), error.message())
+    }
+}
+
+fn parse_move(state: &GameState, move_str: &str) -> Result<(usize, usize), MoveError> {
     if !state.winner.is_empty() {
-        return to_c_string("[]".to_string());
+        return Err(MoveError::GameOver);
     }
-    
+
+    let malformed = || MoveError::Malformed(move_str.to_string());
+    let (row_str, col_str) = move_str.split_once(',').ok_or_else(malformed)?;
+    let row = row_str.trim().parse::<i64>().map_err(|_| malformed())?;
+    let col = col_str.trim().parse::<i64>().map_err(|_| malformed())?;
+
+    let out_of_bounds = || MoveError::OutOfBounds(row, col, state.rows, state.cols);
+    let (row, col) = match (usize::try_from(row), usize::try_from(col)) {
+        (Ok(row), Ok(col)) => (row, col),
+        _ => return Err(out_of_bounds()),
+    };
+    match state.board.get(row).and_then(|cells| cells.get(col)) {
+        None => return Err(out_of_bounds()),
+        Some(&cell) if cell != 0 => return Err(MoveError::Occupied(row, col)),
+        Some(_) => {}
+    }
+    if state.variant == Variant::Ultimate {
+        ultimate::check_board(state, row, col)?;
+    }
+
+    Ok((row, col))
+}
+
+/// The moves the player to move may choose from.
+fn legal_moves(state: &GameState) -> Vec<String> {
+    match state.variant {
+        Variant::Standard => empty_cells(state),
+        Variant::Ultimate => ultimate::legal_moves(state),
+    }
+}
+
+/// Every empty cell as a "row,col" move, row by row.
+fn empty_cells(state: &GameState) -> Vec<String> {
     let mut moves = Vec::new();
-    for row in 0..3 {
-        for col in 0..3 {
-            if state.board[row][col] == 0 {
+    for (row, cells) in state.board.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            if cell == 0 {
                 moves.push(format!("{},{}", row, col));
             }
         }
     }
+    moves
+}
 
-    if moves.len() < 9 && !moves.contains(&"1,1".to_string()) && state.board[1][1] == 0 {
-        eprintln!("🐛 WASM Tic-Tac-Toe Debug:");
-        eprintln!("  Move count: {}", state.move_count);
-        eprintln!("  Center (1,1) value: {}", state.board[1][1]);
-        eprintln!("  Contains 1,1: {}", moves.contains(&"1,1".to_string()));
-        eprintln!("  Moves generated: {:?}", moves);
-    }
+struct TicTacToe;
 
-    to_c_string(serde_json::to_string(&moves).unwrap())
-}
+impl arena_game_sdk::Game for TicTacToe {
+    type State = GameState;
 
-#[no_mangle]
-pub extern "C" fn apply_move(state_ptr: *const c_char, move_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let move_str = from_c_string(move_ptr);
-    
-    let mut state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
+    const NAME: &'static str = "Tic-Tac-Toe";
+    const DESCRIPTION: &'static str =
+        "Tic-tac-toe on a configurable board: classic 3x3 by default, any m,n,k-game up to 26x26 such as Gomoku, or the ultimate variant";
+    /// `hints` and `analysis` only cover boards of up to `solver::MAX_CELLS`
+    /// cells; `capabilities` turns them off for larger boards, where they
+    /// return an `unsupported` error.
+    const CAPABILITIES: Capabilities = Capabilities {
+        hints: true,
+        analysis: true,
+        custom_start: true,
+        ..Capabilities::BASIC
+    };
+
+    fn initial_state() -> GameState {
         GameState {
-            board: [[0; 3]; 3],
+            board: vec![vec![0; 3]; 3],
             current_player: "player1".to_string(),
             move_count: 0,
             winner: "".to_string(),
+            moves: Vec::new(),
+            rows: 3,
+            cols: 3,
+            win_length: 3,
+            variant: Variant::Standard,
+            active_board: None,
         }
-    });
-    
-    if let Some((row_str, col_str)) = move_str.split_once(',') {
-        if let (Ok(row), Ok(col)) = (row_str.parse::<usize>(), col_str.parse::<usize>()) {
-            if row < 3 && col < 3 && state.board[row][col] == 0 {
-                let player_mark = if state.current_player == "player1" { 1 } else { 2 };
-                state.board[row][col] = player_mark;
-                state.move_count += 1;
-                
-
-                state.winner = check_winner(&state.board);
-                
-
-                state.current_player = if state.current_player == "player1" { 
-                    "player2".to_string() 
-                } else { 
-                    "player1".to_string() 
-                };
-            }
+    }
+
+    fn check_state(state: &GameState) -> Result<(), String> {
+        let config = BoardConfig {
+            variant: state.variant,
+            rows: state.rows,
+            cols: state.cols,
+            win_length: state.win_length,
+        };
+        config.validate()?;
+        if state.variant == Variant::Ultimate
+            && (state.rows, state.cols, state.win_length) != (ultimate::BOARD_SIZE, ultimate::BOARD_SIZE, ultimate::SIZE)
+        {
+            return Err(format!("an ultimate board is {0}x{0}", ultimate::BOARD_SIZE));
+        }
+        if state.board.len() != state.rows || state.board.iter().any(|cells| cells.len() != state.cols) {
+            return Err(format!("board does not match its {}x{} size", state.rows, state.cols));
+        }
+        if state.board.iter().flatten().any(|cell| !(0..=2).contains(cell)) {
+            return Err("board cells must be 0 (empty), 1 (X) or 2 (O)".to_string());
+        }
+        if state.active_board.is_some_and(|index| index >= ultimate::BOARD_SIZE) {
+            return Err("active_board must be a sub-board from 0 to 8".to_string());
+        }
+        if state.variant == Variant::Standard && state.active_board.is_some() {
+            return Err("active_board only applies to the ultimate variant".to_string());
         }
+        let to_move = Self::current_player(state);
+        if state.current_player != to_move {
+            return Err(format!(
+                "current_player is {} but {} moves after {} moves",
+                state.current_player, to_move, state.move_count
+            ));
+        }
+        let winner = result(state);
+        if state.winner != winner {
+            let winner = if winner.is_empty() { "no winner yet" } else { winner.as_str() };
+            return Err(format!("winner is '{}' but the board says {}", state.winner, winner));
+        }
+        Ok(())
     }
-    
-    to_c_string(serde_json::to_string(&state).unwrap())
-}
 
-fn check_winner(board: &[[i32; 3]; 3]) -> String {
+    fn capabilities(state: &GameState) -> Capabilities {
+        let solvable = Solver::new(&state.board, state.win_length).is_some();
+        Capabilities { hints: solvable, analysis: solvable, ..Self::CAPABILITIES }
+    }
 
-    for row in 0..3 {
-        if board[row][0] != 0 && board[row][0] == board[row][1] && board[row][1] == board[row][2] {
-            return if board[row][0] == 1 { "player1".to_string() } else { "player2".to_string() };
+    fn legal_moves(state: &GameState) -> Vec<String> {
+        if !state.winner.is_empty() {
+            return Vec::new();
         }
+
+        legal_moves(state)
     }
-    
 
-    for col in 0..3 {
-        if board[0][col] != 0 && board[0][col] == board[1][col] && board[1][col] == board[2][col] {
-            return if board[0][col] == 1 { "player1".to_string() } else { "player2".to_string() };
+    fn apply(state: &mut GameState, move_str: &str) -> Result<(), GameError> {
+        let (row, col) = parse_move(state, move_str)?;
+
+        // The mark and the next player both follow move_count, the same
+        // source current_player() reads, so the two cannot drift apart.
+        let player_mark = if Self::current_player(state) == "player1" { 1 } else { 2 };
+        state.board[row][col] = player_mark;
+        state.move_count += 1;
+        state.moves.push(format!("{},{}", row, col));
+
+        state.winner = result(state);
+        if state.variant == Variant::Ultimate {
+            state.active_board = ultimate::next_board(&state.board, row, col);
         }
+
+        state.current_player = Self::current_player(state);
+
+        Ok(())
     }
-    
 
-    if board[0][0] != 0 && board[0][0] == board[1][1] && board[1][1] == board[2][2] {
-        return if board[0][0] == 1 { "player1".to_string() } else { "player2".to_string() };
+    fn outcome(state: &GameState) -> Outcome {
+        match state.winner.as_str() {
+            "" => Outcome::Ongoing,
+            "draw" => Outcome::Draw,
+            winner => Outcome::Winner(winner.to_string()),
+        }
     }
-    
-    if board[0][2] != 0 && board[0][2] == board[1][1] && board[1][1] == board[2][0] {
-        return if board[0][2] == 1 { "player1".to_string() } else { "player2".to_string() };
+
+    fn current_player(state: &GameState) -> String {
+        let current_player = if state.move_count.is_multiple_of(2) {
+            "player1"
+        } else {
+            "player2"
+        };
+
+        current_player.to_string()
     }
-    
 
-    let mut empty_cells = 0;
-    for row in 0..3 {
-        for col in 0..3 {
-            if board[row][col] == 0 {
-                empty_cells += 1;
+    fn render(state: &GameState) -> String {
+        if state.variant == Variant::Ultimate {
+            return ultimate::render(state);
+        }
+
+        // Labels get as wide as the largest index so 10x10 and bigger boards
+        // stay aligned; a 3x3 board renders as it always has.
+        let label_width = (state.rows.max(state.cols).saturating_sub(1)).to_string().len();
+        let header: String = (0..state.cols).map(|col| format!("{:<width$}", col, width = label_width + 3)).collect();
+        let header = format!("{}{}\n", " ".repeat(label_width + 1), header.trim_end());
+        let separator = vec!["-".repeat(label_width + 2); state.cols].join("|");
+
+        let mut output = String::new();
+        output.push_str(&header);
+
+        for (row, cells) in state.board.iter().enumerate() {
+            output.push_str(&format!("{:<width$} ", row, width = label_width));
+            for (col, &cell) in cells.iter().enumerate() {
+                let cell = match cell {
+                    1 => "X",
+                    2 => "O",
+                    _ => " ",
+                };
+                output.push_str(&format!(" {:^width$} ", cell, width = label_width));
+                if col + 1 < cells.len() {
+                    output.push('|');
+                }
+            }
+            output.push_str(&format!(" {}\n", row));
+
+            if row + 1 < state.board.len() {
+                output.push_str(&format!("{}{}\n", " ".repeat(label_width + 1), separator));
             }
         }
+
+        output.push_str(&header);
+
+        output
     }
-    
-    if empty_cells == 0 {
-        "draw".to_string()
-    } else {
-        "".to_string()
-    }
-}
 
-#[no_mangle]
-pub extern "C" fn is_game_over(state_ptr: *const c_char) -> i32 {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            board: [[0; 3]; 3],
-            current_player: "player1".to_string(),
-            move_count: 0,
-            winner: "".to_string(),
+    fn transcript(state: &GameState) -> String {
+        let mut transcript = String::new();
+        transcript.push_str("=== TIC-TAC-TOE GAME TRANSCRIPT ===\n");
+        match state.variant {
+            Variant::Standard => transcript.push_str(&format!(
+                "Board: {}x{}, {} in a row to win\n",
+                state.rows, state.cols, state.win_length
+            )),
+            Variant::Ultimate => transcript.push_str("Board: ultimate tic-tac-toe, 9x9 in nine 3x3 sub-boards\n"),
         }
-    });
-    
-    if !state.winner.is_empty() { 1 } else { 0 }
-}
+        transcript.push_str(&format!("Move count: {}\n", state.move_count));
+        transcript.push_str(&format!("Current player: {}\n", state.current_player));
+        transcript.push_str(&format!("Winner: {}\n", if state.winner.is_empty() { "None" } else { &state.winner }));
 
-#[no_mangle]
-pub extern "C" fn get_winner(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            board: [[0; 3]; 3],
-            current_player: "player1".to_string(),
-            move_count: 0,
-            winner: "".to_string(),
+        transcript.push_str("Board state:\n");
+        for cells in &state.board {
+            transcript.push_str("  ");
+            for &cell in cells {
+                let symbol = match cell {
+                    1 => "X",
+                    2 => "O",
+                    _ => "."
+                };
+                transcript.push_str(&format!("{} ", symbol));
+            }
+            transcript.push('\n');
         }
-    });
-    
-    to_c_string(state.winner)
+
+        let valid_moves = legal_moves(state);
+
+        transcript.push_str(&format!("Valid moves ({}): {:?}\n", valid_moves.len(), valid_moves));
+        transcript.push_str("===================================\n");
+
+        transcript
+    }
 }
 
+arena_game_sdk::export_game!(TicTacToe);
+arena_game_sdk::embed_metadata!("../metadata.json");
+
+/// Starts a game on a custom board, e.g. `{"rows": 15, "cols": 15,
+/// "win_length": 5}` for Gomoku. Missing fields default to 3; a bad config
+/// returns an `invalid_config` error result.
 #[no_mangle]
-pub extern "C" fn render(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            board: [[0; 3]; 3],
-            current_player: "player1".to_string(),
-            move_count: 0,
-            winner: "".to_string(),
-        }
-    });
-    
-    let mut output = String::new();
-    output.push_str("  0   1   2\n");
-    
-    for row in 0..3 {
-        output.push_str(&format!("{} ", row));
-        for col in 0..3 {
-            let cell = match state.board[row][col] {
-                1 => "X",
-                2 => "O",
-                _ => " ",
-            };
-            output.push_str(&format!(" {} ", cell));
-            if col < 2 {
-                output.push('|');
+pub extern "C" fn get_initial_state_with_config(config_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let config_str = from_c_string(config_ptr);
+        let config: BoardConfig = match serde_json::from_str(&config_str) {
+            Ok(config) => config,
+            Err(error) => {
+                let message = format!("config must be a JSON object with rows, cols and win_length: {}", error);
+                return to_c_string(GameError::new("invalid_config", message).to_result());
             }
+        };
+        if let Err(message) = config.validate() {
+            return to_c_string(GameError::new("invalid_config", message).to_result());
         }
-        output.push_str(&format!(" {}\n", row));
-        
-        if row < 2 {
-            output.push_str("  ---|---|---\n");
-        }
+
+        write_state::<TicTacToe>(&config.initial_state())
+    })
+}
+
+/// The winner the board shows under the state's rules, in the same form
+/// as `GameState::winner`.
+fn result(state: &GameState) -> String {
+    match state.variant {
+        Variant::Standard => check_winner(&state.board, state.win_length),
+        Variant::Ultimate => ultimate::winner(&state.board),
     }
-    
-    output.push_str("  0   1   2\n");
-    
-    to_c_string(output)
 }
 
-#[no_mangle]
-pub extern "C" fn get_game_name() -> *mut c_char {
-    to_c_string("Tic-Tac-Toe".to_string())
+fn player_name(mark: i32) -> String {
+    if mark == 1 { "player1".to_string() } else { "player2".to_string() }
 }
 
-#[no_mangle]
-pub extern "C" fn get_current_player(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            board: [[0; 3]; 3],
-            current_player: "player1".to_string(),
-            move_count: 0,
-            winner: "".to_string(),
+/// Looks for `win_length` equal player marks in a row, column or diagonal
+/// from every occupied cell. Returns the winner, "draw" on a full board, or
+/// "" while the game goes on. Values other than 1 and 2 fill a cell without
+/// counting towards a line.
+pub(crate) fn check_winner(board: &[Vec<i32>], win_length: usize) -> String {
+    const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+    for (row, cells) in board.iter().enumerate() {
+        for (col, &mark) in cells.iter().enumerate() {
+            if mark != 1 && mark != 2 {
+                continue;
+            }
+
+            for (row_step, col_step) in DIRECTIONS {
+                let run = (1..win_length as isize)
+                    .take_while(|&i| {
+                        let r = row as isize + row_step * i;
+                        let c = col as isize + col_step * i;
+                        r >= 0 && c >= 0 && board.get(r as usize).and_then(|line| line.get(c as usize)) == Some(&mark)
+                    })
+                    .count();
+                if run + 1 >= win_length {
+                    return player_name(mark);
+                }
+            }
         }
-    });
+    }
 
-    let current_player = if state.move_count % 2 == 0 {
-        "player1"
+    if board.iter().flatten().all(|&cell| cell != 0) {
+        "draw".to_string()
     } else {
-        "player2"
-    };
+        "".to_string()
+    }
+}
 
-    to_c_string(current_player.to_string())
+fn unsupported(state: &GameState) -> String {
+    let message = format!(
+        "hints and analysis only cover boards of up to {} cells, and this board is {}x{}",
+        solver::MAX_CELLS,
+        state.rows,
+        state.cols
+    );
+    GameError::new("unsupported", message).to_result()
 }
 
+/// Perfect-play value of every legal move for the player to move: "win",
+/// "draw" or "loss", assuming both sides play perfectly afterwards.
 #[no_mangle]
-pub extern "C" fn get_game_description() -> *mut c_char {
-    to_c_string("Classic 3x3 tic-tac-toe game".to_string())
+pub extern "C" fn get_move_values(state_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let state = match read_state::<TicTacToe>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        let mut solver = match Solver::new(&state.board, state.win_length) {
+            Some(solver) => solver,
+            None => return to_c_string(unsupported(&state)),
+        };
+        let moves: Vec<serde_json::Value> = if state.winner.is_empty() {
+            solver
+                .move_values(&state.board)
+                .into_iter()
+                .map(|((row, col), value)| serde_json::json!({ "move": format!("{},{}", row, col), "value": value.as_str() }))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let output = serde_json::json!({
+            "value": solver.solve(&state.board).as_str(),
+            "moves": moves,
+        });
+
+        to_c_string(output.to_string())
+    })
 }
 
+/// Replays `moves` and marks each one "optimal" if it kept the best value
+/// available to its player, or "blunder" if it threw a win or a draw away.
 #[no_mangle]
-pub extern "C" fn log_transcript(state_ptr: *const c_char) -> *mut c_char {
-    let state_str = from_c_string(state_ptr);
-    let state: GameState = serde_json::from_str(&state_str).unwrap_or_else(|_| {
-        GameState {
-            board: [[0; 3]; 3],
+pub extern "C" fn score_game(state_ptr: *const c_char) -> *mut c_char {
+    guard(|| {
+        let state = match read_state::<TicTacToe>(state_ptr) {
+            Ok(state) => state,
+            Err(error) => return to_c_string(error.to_result()),
+        };
+
+        let mut solver = match Solver::new(&state.board, state.win_length) {
+            Some(solver) => solver,
+            None => return to_c_string(unsupported(&state)),
+        };
+        let mut replay = GameState {
+            board: vec![vec![0; state.cols]; state.rows],
             current_player: "player1".to_string(),
             move_count: 0,
             winner: "".to_string(),
-        }
-    });
-
-    let mut transcript = String::new();
-    transcript.push_str(&format!("=== TIC-TAC-TOE GAME TRANSCRIPT ===\n"));
-    transcript.push_str(&format!("Move count: {}\n", state.move_count));
-    transcript.push_str(&format!("Current player: {}\n", state.current_player));
-    transcript.push_str(&format!("Winner: {}\n", if state.winner.is_empty() { "None" } else { &state.winner }));
-
-    transcript.push_str("Board state:\n");
-    for row in 0..3 {
-        transcript.push_str("  ");
-        for col in 0..3 {
-            let symbol = match state.board[row][col] {
-                1 => "X",
-                2 => "O",
-                _ => "."
+            moves: Vec::new(),
+            rows: state.rows,
+            cols: state.cols,
+            win_length: state.win_length,
+            variant: state.variant,
+            active_board: None,
+        };
+        let mut scored = Vec::new();
+        let mut blunders = [0, 0];
+
+        for (i, move_str) in state.moves.iter().enumerate() {
+            let (row, col) = match parse_move(&replay, move_str) {
+                Ok(cell) => cell,
+                Err(_) => break,
             };
-            transcript.push_str(&format!("{} ", symbol));
+
+            let values = solver.move_values(&replay.board);
+            let best = values.iter().map(|(_, value)| *value).max().unwrap_or(solver::Value::Draw);
+            let played = values
+                .iter()
+                .find(|(cell, _)| *cell == (row, col))
+                .map(|(_, value)| *value)
+                .unwrap_or(best);
+            let mark = solver::mark_to_move(&replay.board);
+            if played < best {
+                blunders[mark as usize - 1] += 1;
+            }
+
+            scored.push(serde_json::json!({
+                "ply": i + 1,
+                "move": move_str.trim(),
+                "player": if mark == 1 { "player1" } else { "player2" },
+                "value": played.as_str(),
+                "best_value": best.as_str(),
+                "classification": if played < best { "blunder" } else { "optimal" },
+            }));
+
+            replay.board[row][col] = mark;
+            replay.winner = check_winner(&replay.board, replay.win_length);
         }
-        transcript.push_str("\n");
+
+        let player1_moves = scored.len().div_ceil(2);
+        let player2_moves = scored.len() / 2;
+        let output = serde_json::json!({
+            "moves": scored,
+            "player1": { "moves": player1_moves, "optimal": player1_moves - blunders[0], "blunders": blunders[0] },
+            "player2": { "moves": player2_moves, "optimal": player2_moves - blunders[1], "blunders": blunders[1] },
+        });
+
+        to_c_string(output.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use arena_game_sdk::ffi::test_util::{self, c, take};
+    use arena_game_sdk::Game;
+
+    use super::*;
+
+    fn configured(config: &str) -> String {
+        take(get_initial_state_with_config(c(config).as_ptr()))
     }
 
-    let mut valid_moves = Vec::new();
-    for row in 0..3 {
-        for col in 0..3 {
-            if state.board[row][col] == 0 {
-                valid_moves.push(format!("{},{}", row, col));
-            }
+    fn play(state: &str, moves: &[&str]) -> String {
+        moves.iter().fold(state.to_string(), |state, move_str| test_util::play(apply_move, &state, move_str))
+    }
+
+    fn rejection(state: &str, move_str: &str) -> (String, String) {
+        let result = take(apply_move(c(state).as_ptr(), c(move_str).as_ptr()));
+        let error: serde_json::Value = serde_json::from_str(result.strip_prefix("ERROR: ").unwrap()).unwrap();
+        (error["code"].as_str().unwrap().to_string(), error["message"].as_str().unwrap().to_string())
+    }
+
+    fn code(state: &str, move_str: &str) -> String {
+        rejection(state, move_str).0
+    }
+
+    #[test]
+    fn unreadable_moves_are_malformed() {
+        let state = take(get_initial_state());
+        for input in ["11", "a,b", "", "1,", "1.5,1"] {
+            assert_eq!(code(&state, input), "malformed", "{}", input);
+        }
+        assert_eq!(
+            rejection(&state, "middle").1,
+            "'middle' is not a move; use \"row,col\" counting from 0, e.g. \"1,1\""
+        );
+    }
+
+    #[test]
+    fn cells_off_the_board_are_out_of_bounds() {
+        let state = take(get_initial_state());
+        assert_eq!(code(&state, "3,0"), "out_of_bounds");
+        assert_eq!(code(&state, "0,3"), "out_of_bounds");
+        assert_eq!(
+            rejection(&state, "-1,2"),
+            (
+                "out_of_bounds".to_string(),
+                "-1,2 is off the board; rows go from 0 to 2 and columns from 0 to 2".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn taken_cells_and_finished_games_are_refused() {
+        let state = play(&take(get_initial_state()), &["1,1"]);
+        assert_eq!(rejection(&state, " 1 , 1 "), ("occupied".to_string(), "cell 1,1 is already taken".to_string()));
+
+        let won = play(&state, &["0,0", "0,1", "2,2", "2,1"]);
+        assert_eq!(code(&won, "2,0"), "game_over");
+    }
+
+    fn json(ptr: *mut c_char) -> serde_json::Value {
+        serde_json::from_str(&take(ptr)).unwrap()
+    }
+
+    #[test]
+    fn move_values_cover_every_empty_cell() {
+        let values = json(get_move_values(c(&take(get_initial_state())).as_ptr()));
+        assert_eq!(values["value"], "draw");
+        assert_eq!(values["moves"].as_array().unwrap().len(), 9);
+        assert_eq!(values["moves"][4], serde_json::json!({ "move": "1,1", "value": "draw" }));
+
+        let won = play(&take(get_initial_state()), &["0,0", "1,0", "0,1", "1,1", "0,2"]);
+        let values = json(get_move_values(c(&won).as_ptr()));
+        assert_eq!(values["value"], "loss");
+        assert_eq!(values["moves"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn score_game_flags_the_move_that_threw_the_draw_away() {
+        let state = play(&take(get_initial_state()), &["1,1", "0,1", "0,0", "2,2", "2,0"]);
+        let score = json(score_game(c(&state).as_ptr()));
+        let classes: Vec<&str> =
+            score["moves"].as_array().unwrap().iter().map(|m| m["classification"].as_str().unwrap()).collect();
+        assert_eq!(classes, ["optimal", "blunder", "optimal", "optimal", "optimal"]);
+        assert_eq!(score["moves"][1]["value"], "loss");
+        assert_eq!(score["moves"][1]["best_value"], "draw");
+        assert_eq!(score["player1"], serde_json::json!({ "moves": 3, "optimal": 3, "blunders": 0 }));
+        assert_eq!(score["player2"], serde_json::json!({ "moves": 2, "optimal": 1, "blunders": 1 }));
+    }
+
+    fn board_with(rows: usize, cols: usize, marks: &[(usize, usize, i32)]) -> Vec<Vec<i32>> {
+        let mut board = vec![vec![0; cols]; rows];
+        for &(row, col, mark) in marks {
+            board[row][col] = mark;
+        }
+        board
+    }
+
+    #[test]
+    fn a_line_of_win_length_wins_in_every_direction() {
+        let lines: [[(usize, usize); 5]; 4] = [
+            [(7, 3), (7, 4), (7, 5), (7, 6), (7, 7)],
+            [(2, 14), (3, 14), (4, 14), (5, 14), (6, 14)],
+            [(10, 10), (11, 11), (12, 12), (13, 13), (14, 14)],
+            [(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)],
+        ];
+        for line in lines {
+            let marks: Vec<(usize, usize, i32)> = line.iter().map(|&(row, col)| (row, col, 2)).collect();
+            assert_eq!(check_winner(&board_with(15, 15, &marks), 5), "player2", "{:?}", line);
+            assert_eq!(check_winner(&board_with(15, 15, &marks[..4]), 5), "", "{:?}", line);
         }
     }
 
-    transcript.push_str(&format!("Valid moves ({}): {:?}\n", valid_moves.len(), valid_moves));
-    transcript.push_str("===================================\n");
+    #[test]
+    fn lines_do_not_wrap_and_only_player_marks_count() {
+        let wrapped = board_with(15, 15, &[(0, 12), (0, 13), (0, 14), (1, 0), (1, 1)].map(|(r, c)| (r, c, 1)));
+        assert_eq!(check_winner(&wrapped, 5), "");
+
+        assert_eq!(check_winner(&[vec![1, 3, 1, 1, 0]], 3), "");
+        assert_eq!(check_winner(&[vec![1, 3, 2, 3]], 3), "draw");
+        assert_eq!(check_winner(&board_with(3, 5, &[(0, 4, 1), (1, 4, 1), (2, 4, 1)]), 3), "player1");
+        assert_eq!(check_winner(&board_with(2, 2, &[(1, 0, 1)]), 1), "player1");
+    }
+
+    #[test]
+    fn configs_set_the_board_and_bad_ones_are_refused() {
+        let state: GameState = serde_json::from_str(&configured(r#"{"rows": 15, "cols": 15, "win_length": 5}"#)).unwrap();
+        assert_eq!((state.board.len(), state.board[0].len(), state.win_length), (15, 15, 5));
 
-    to_c_string(transcript)
+        let state: GameState = serde_json::from_str(&configured(r#"{"cols": 4}"#)).unwrap();
+        assert_eq!((state.rows, state.cols, state.win_length), (3, 4, 3));
+
+        let state: GameState = serde_json::from_str(&configured(r#"{"variant": "ultimate", "rows": 4}"#)).unwrap();
+        assert_eq!((state.rows, state.cols, state.win_length), (9, 9, 3));
+
+        for config in [
+            r#"{"rows": 0}"#,
+            r#"{"rows": 27, "cols": 27}"#,
+            r#"{"rows": 3, "cols": 4, "win_length": 5}"#,
+            r#"{"win_length": 0}"#,
+            r#"{"rows": -1}"#,
+            "15x15",
+        ] {
+            let result = configured(config);
+            assert!(result.starts_with(r#"ERROR: {"code":"invalid_config""#), "{}: {}", config, result);
+        }
+    }
+
+    #[test]
+    fn states_that_do_not_match_their_size_are_invalid() {
+        let state = r#"{"board":[[0,0,0],[0,0,0]],"current_player":"player1","move_count":0,"winner":""}"#;
+        let result = take(get_valid_moves(c(state).as_ptr()));
+        assert!(result.starts_with(r#"ERROR: {"code":"invalid_state""#), "{}", result);
+        assert!(result.contains("3x3"), "{}", result);
+    }
+
+    #[test]
+    fn states_that_contradict_themselves_are_invalid() {
+        let invalid = |state: &str, reason: &str| {
+            let result = take(get_valid_moves(c(state).as_ptr()));
+            assert!(result.starts_with(r#"ERROR: {"code":"invalid_state""#), "{}", result);
+            assert!(result.contains(reason), "{}", result);
+        };
+
+        invalid(
+            r#"{"board":[[1,0,0],[0,0,0],[0,0,0]],"current_player":"player1","move_count":1,"winner":""}"#,
+            "player2 moves after 1 moves",
+        );
+        invalid(
+            r#"{"board":[[1,1,1],[2,2,0],[0,0,0]],"current_player":"player2","move_count":5,"winner":""}"#,
+            "the board says player1",
+        );
+        invalid(
+            r#"{"board":[[0,0,0],[0,0,0],[0,0,0]],"current_player":"player1","move_count":0,"winner":"player2"}"#,
+            "no winner yet",
+        );
+        invalid(
+            r#"{"board":[[0,0,0],[0,0,0],[0,0,0]],"current_player":"player1","move_count":0,"winner":"","active_board":4}"#,
+            "only applies to the ultimate variant",
+        );
+
+        let state = play(&take(get_initial_state()), &["1,1", "0,0"]);
+        let state: GameState = serde_json::from_str(&state).unwrap();
+        assert_eq!((state.board[1][1], state.board[0][0], state.current_player.as_str()), (1, 2, "player1"));
+    }
+
+    fn legal(state: &str) -> Vec<String> {
+        serde_json::from_str(&take(get_valid_moves(c(state).as_ptr()))).unwrap()
+    }
+
+    #[test]
+    fn ultimate_moves_are_sent_to_the_matching_sub_board() {
+        let state = configured(r#"{"variant": "ultimate"}"#);
+        assert_eq!(legal(&state).len(), 81);
+
+        let state = play(&state, &["4,7"]);
+        assert_eq!(legal(&state), ["3,3", "3,4", "3,5", "4,3", "4,4", "4,5", "5,3", "5,4", "5,5"]);
+        assert_eq!(
+            rejection(&state, "0,0"),
+            ("wrong_board".to_string(), "the last move sends you to sub-board 4 (rows 3-5, columns 3-5)".to_string())
+        );
+        let rendered = TicTacToe::render(&serde_json::from_str(&state).unwrap());
+        assert!(rendered.ends_with("Next move in sub-board 4 (rows 3-5, columns 3-5)\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn ultimate_closed_sub_boards_are_refused_and_free_the_next_move() {
+        let mut state: GameState = serde_json::from_str(&configured(r#"{"variant": "ultimate"}"#)).unwrap();
+        state.board[0] = vec![1, 1, 1, 0, 0, 0, 0, 0, 0];
+        state.board[3][3] = 2;
+        state.board[3][4] = 2;
+        state.move_count = 5;
+        state.current_player = "player2".to_string();
+        let state = serde_json::to_string(&state).unwrap();
+
+        let (code, message) = rejection(&state, "1,1");
+        assert_eq!(code, "board_closed");
+        assert_eq!(message, "sub-board 0 (rows 0-2, columns 0-2) is already decided; play in an open one");
+
+        let freed = play(&state, &["3,6"]);
+        let moves = legal(&freed);
+        assert_eq!(moves.len(), 81 - 9 - 3);
+        assert!(!moves.contains(&"1,1".to_string()));
+        assert!(moves.contains(&"8,8".to_string()));
+    }
+
+    #[test]
+    fn the_embedded_metadata_describes_this_game() {
+        let metadata = take(get_metadata());
+        assert_eq!(metadata, include_str!("../metadata.json"));
+        assert_eq!(&ARENA_METADATA[..], metadata.as_bytes());
+
+        let metadata: serde_json::Value = serde_json::from_str(&metadata).unwrap();
+        assert_eq!(metadata["name"], TicTacToe::NAME);
+        assert!(metadata["aiPrompts"].is_object());
+    }
+
+    #[cfg(feature = "binary-state")]
+    #[test]
+    fn binary_states_keep_every_field() {
+        let round_trip = |state: &str| {
+            let buffer = encode_state(c(state).as_ptr());
+            let bytes = unsafe {
+                let len = u32::from_le_bytes(std::slice::from_raw_parts(buffer, 4).try_into().unwrap());
+                std::slice::from_raw_parts(buffer.add(4), len as usize).to_vec()
+            };
+            arena_free(buffer);
+            take(decode_state(bytes.as_ptr(), bytes.len()))
+        };
+
+        let gomoku = play(&configured(r#"{"rows": 15, "cols": 15, "win_length": 5}"#), &["7,7"]);
+        assert_eq!(round_trip(&gomoku), gomoku);
+        let ultimate = play(&configured(r#"{"variant": "ultimate"}"#), &["4,7"]);
+        assert!(ultimate.contains("active_board"));
+        assert_eq!(round_trip(&ultimate), ultimate);
+    }
+
+    #[test]
+    fn solver_exports_refuse_large_boards_as_unsupported() {
+        let state = configured(r#"{"rows": 4, "cols": 4, "win_length": 4}"#);
+        for result in [take(get_move_values(c(&state).as_ptr())), take(score_game(c(&state).as_ptr()))] {
+            assert!(result.starts_with(r#"ERROR: {"code":"unsupported""#), "{}", result);
+            assert!(result.contains("4x4"), "{}", result);
+        }
+        assert!(!TicTacToe::DESCRIPTION.contains("cells"));
+
+        let capabilities = |state: &str| -> serde_json::Value {
+            serde_json::from_str(&take(get_state_capabilities(c(state).as_ptr()))).unwrap()
+        };
+        for state in [state, configured(r#"{"variant": "ultimate"}"#)] {
+            let capabilities = capabilities(&state);
+            assert_eq!((&capabilities["hints"], &capabilities["analysis"]), (&false.into(), &false.into()), "{}", state);
+            assert_eq!(capabilities["custom_start"], true);
+        }
+        let small = capabilities(&configured(r#"{"rows": 3, "cols": 4}"#));
+        assert_eq!((&small["hints"], &small["analysis"]), (&true.into(), &true.into()));
+    }
 }
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::check_winner;
+
+/// Game-theoretic value of a position or move for the player it belongs to,
+/// assuming perfect play from both sides afterwards.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Value {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Value {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Value::Loss => "loss",
+            Value::Draw => "draw",
+            Value::Win => "win",
+        }
+    }
+
+    fn flip(self) -> Value {
+        match self {
+            Value::Loss => Value::Win,
+            Value::Draw => Value::Draw,
+            Value::Win => Value::Loss,
+        }
+    }
+}
+
+/// Largest board the solver will take on. A 3x4 board has a few hundred
+/// thousand positions; anything bigger is too slow to solve on demand.
+pub const MAX_CELLS: usize = 12;
+
+/// Plain minimax over the whole game tree, memoized on the board. The 3x3
+/// game has fewer than 6,000 reachable positions.
+pub struct Solver {
+    win_length: usize,
+    memo: HashMap<u64, Value>,
+}
+
+/// The board as a base-3 number, one digit per cell.
+fn key(board: &[Vec<i32>]) -> u64 {
+    board.iter().flatten().fold(0, |key, &cell| key * 3 + cell as u64)
+}
+
+/// X (1) moves whenever both sides have the same number of marks.
+pub fn mark_to_move(board: &[Vec<i32>]) -> i32 {
+    let filled = board.iter().flatten().filter(|&&cell| cell != 0).count();
+    if filled % 2 == 0 { 1 } else { 2 }
+}
+
+impl Solver {
+    /// A solver for boards that need `win_length` in a row, or `None` if
+    /// the board has more than `MAX_CELLS` cells.
+    pub fn new(board: &[Vec<i32>], win_length: usize) -> Option<Solver> {
+        if board.iter().map(|cells| cells.len()).sum::<usize>() > MAX_CELLS {
+            return None;
+        }
+        Some(Solver { win_length, memo: HashMap::new() })
+    }
+
+    /// Value of the position for the side to move.
+    pub fn solve(&mut self, board: &[Vec<i32>]) -> Value {
+        if let Some(&value) = self.memo.get(&key(board)) {
+            return value;
+        }
+
+        let value = match check_winner(board, self.win_length).as_str() {
+            // The previous move ended the game, so the side to move has lost.
+            "player1" | "player2" => Value::Loss,
+            "draw" => Value::Draw,
+            _ => self.move_values(board).into_iter().map(|(_, value)| value).max().unwrap_or(Value::Draw),
+        };
+
+        self.memo.insert(key(board), value);
+        value
+    }
+
+    /// Value of every empty cell for the side to move, in board order.
+    pub fn move_values(&mut self, board: &[Vec<i32>]) -> Vec<((usize, usize), Value)> {
+        let mark = mark_to_move(board);
+        let mut next = board.to_vec();
+        let mut values = Vec::new();
+
+        for (row, cells) in board.iter().enumerate() {
+            for (col, &cell) in cells.iter().enumerate() {
+                if cell == 0 {
+                    next[row][col] = mark;
+                    values.push(((row, col), self.solve(&next).flip()));
+                    next[row][col] = 0;
+                }
+            }
+        }
+
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solver(board: &[Vec<i32>]) -> Solver {
+        Solver::new(board, 3).unwrap()
+    }
+
+    #[test]
+    fn the_empty_board_is_a_draw_whatever_x_opens_with() {
+        let empty = vec![vec![0; 3]; 3];
+        let mut solver = solver(&empty);
+        assert_eq!(solver.solve(&empty), Value::Draw);
+
+        let values = solver.move_values(&empty);
+        assert_eq!(values.len(), 9);
+        assert!(values.iter().all(|(_, value)| *value == Value::Draw));
+    }
+
+    #[test]
+    fn values_each_move_for_the_side_to_move() {
+        let board = vec![vec![1, 1, 0], vec![2, 2, 0], vec![0, 0, 0]];
+        let mut solver = solver(&board);
+        assert_eq!(mark_to_move(&board), 1);
+        assert_eq!(solver.solve(&board), Value::Win);
+
+        let values: HashMap<(usize, usize), Value> = solver.move_values(&board).into_iter().collect();
+        assert_eq!(values[&(0, 2)], Value::Win);
+        assert_eq!(values[&(2, 0)], Value::Loss);
+    }
+
+    #[test]
+    fn a_finished_game_is_lost_for_the_side_to_move() {
+        let board = vec![vec![1, 1, 1], vec![2, 2, 0], vec![0, 0, 0]];
+        assert_eq!(solver(&board).solve(&board), Value::Loss);
+    }
+
+    #[test]
+    fn refuses_boards_over_the_cell_limit() {
+        assert!(Solver::new(&vec![vec![0; 4]; 3], 3).is_some());
+        assert!(Solver::new(&vec![vec![0; 4]; 4], 3).is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{check_winner, GameState, MoveError};
+
+/// Cells along each side of a sub-board, and sub-boards along each side of
+/// the big board.
+pub const SIZE: usize = 3;
+pub const BOARD_SIZE: usize = SIZE * SIZE;
+
+/// Marks a drawn sub-board on the big board. `check_winner` only counts
+/// player marks, so a drawn sub-board blocks lines through it.
+const DRAWN: i32 = 3;
+
+/// The sub-board (0-8, row by row) a cell of the 9x9 board belongs to.
+pub fn board_of(row: usize, col: usize) -> usize {
+    (row / SIZE) * SIZE + col / SIZE
+}
+
+/// The sub-board the next player is sent to by a move on (row, col): the
+/// one in the same position as the cell within its own sub-board.
+fn target_of(row: usize, col: usize) -> usize {
+    (row % SIZE) * SIZE + col % SIZE
+}
+
+/// Human-readable extent of a sub-board, e.g. "rows 3-5, columns 6-8".
+pub fn describe(index: usize) -> String {
+    let top = (index / SIZE) * SIZE;
+    let left = (index % SIZE) * SIZE;
+    format!("rows {}-{}, columns {}-{}", top, top + SIZE - 1, left, left + SIZE - 1)
+}
+
+fn sub_board(board: &[Vec<i32>], index: usize) -> Vec<Vec<i32>> {
+    let top = (index / SIZE) * SIZE;
+    let left = (index % SIZE) * SIZE;
+    (top..top + SIZE)
+        .map(|row| (left..left + SIZE).map(|col| board.get(row).and_then(|cells| cells.get(col)).copied().unwrap_or(0)).collect())
+        .collect()
+}
+
+/// "player1", "player2", "draw" or "" for a sub-board still in play.
+pub fn sub_winner(board: &[Vec<i32>], index: usize) -> String {
+    check_winner(&sub_board(board, index), SIZE)
+}
+
+/// The 3x3 board of sub-board results that decides the game.
+fn meta_board(board: &[Vec<i32>]) -> Vec<Vec<i32>> {
+    (0..SIZE)
+        .map(|row| {
+            (0..SIZE)
+                .map(|col| match sub_winner(board, row * SIZE + col).as_str() {
+                    "player1" => 1,
+                    "player2" => 2,
+                    "draw" => DRAWN,
+                    _ => 0,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Winner of the whole game: three won sub-boards in a row, or "draw" once
+/// every sub-board is decided without one.
+pub fn winner(board: &[Vec<i32>]) -> String {
+    check_winner(&meta_board(board), SIZE)
+}
+
+/// Where the player after a move on (row, col) has to play: the sub-board
+/// it points at, or anywhere (`None`) if that one is already decided.
+pub fn next_board(board: &[Vec<i32>], row: usize, col: usize) -> Option<usize> {
+    let target = target_of(row, col);
+    if sub_winner(board, target).is_empty() {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+/// Checks that (row, col) lies in a sub-board the player to move may use.
+pub fn check_board(state: &GameState, row: usize, col: usize) -> Result<(), MoveError> {
+    let index = board_of(row, col);
+    match state.active_board {
+        Some(active) if active != index => Err(MoveError::WrongBoard(active)),
+        _ if !sub_winner(&state.board, index).is_empty() => Err(MoveError::BoardClosed(index)),
+        _ => Ok(()),
+    }
+}
+
+/// Empty cells the player to move may choose, row by row.
+pub fn legal_moves(state: &GameState) -> Vec<String> {
+    let open: Vec<bool> = (0..BOARD_SIZE)
+        .map(|index| state.active_board.is_none_or(|active| active == index) && sub_winner(&state.board, index).is_empty())
+        .collect();
+
+    let mut moves = Vec::new();
+    for (row, cells) in state.board.iter().enumerate() {
+        for (col, &cell) in cells.iter().enumerate() {
+            if cell == 0 && open[board_of(row, col)] {
+                moves.push(format!("{},{}", row, col));
+            }
+        }
+    }
+    moves
+}
+
+/// The 9x9 board with heavier lines between sub-boards, followed by the
+/// sub-board results and where the next move has to go.
+pub fn render(state: &GameState) -> String {
+    let header = "    0 1 2   3 4 5   6 7 8\n";
+    let mut output = String::new();
+    output.push_str(header);
+
+    for (row, cells) in state.board.iter().enumerate() {
+        if row > 0 && row % SIZE == 0 {
+            output.push_str("    ------+-------+------\n");
+        }
+        output.push_str(&format!("{}   ", row));
+        for (col, &cell) in cells.iter().enumerate() {
+            if col > 0 && col % SIZE == 0 {
+                output.push_str("| ");
+            }
+            let cell = match cell {
+                1 => "X",
+                2 => "O",
+                _ => ".",
+            };
+            output.push_str(&format!("{} ", cell));
+        }
+        output.push_str(&format!("  {}\n", row));
+    }
+    output.push_str(header);
+
+    output.push_str("\nSub-boards:\n");
+    for row in 0..SIZE {
+        output.push_str("  ");
+        for col in 0..SIZE {
+            let result = match sub_winner(&state.board, row * SIZE + col).as_str() {
+                "player1" => "X",
+                "player2" => "O",
+                "draw" => "=",
+                _ => ".",
+            };
+            output.push_str(&format!("{} ", result));
+        }
+        output.push('\n');
+    }
+
+    if state.winner.is_empty() {
+        match state.active_board {
+            Some(index) => output.push_str(&format!("Next move in sub-board {} ({})\n", index, describe(index))),
+            None => output.push_str("Next move in any open sub-board\n"),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 9x9 board with the given sub-boards won by `mark` along their top
+    /// row, and `drawn` ones filled without a line.
+    fn board_with(won: &[(usize, i32)], drawn: &[usize]) -> Vec<Vec<i32>> {
+        let mut board = vec![vec![0; BOARD_SIZE]; BOARD_SIZE];
+        for &(index, mark) in won {
+            let (top, left) = ((index / SIZE) * SIZE, (index % SIZE) * SIZE);
+            board[top][left..left + SIZE].fill(mark);
+        }
+        for &index in drawn {
+            let (top, left) = ((index / SIZE) * SIZE, (index % SIZE) * SIZE);
+            let marks = [[1, 2, 1], [1, 2, 2], [2, 1, 1]];
+            for (row, line) in marks.iter().enumerate() {
+                for (col, &mark) in line.iter().enumerate() {
+                    board[top + row][left + col] = mark;
+                }
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn a_cell_sends_the_next_player_to_the_matching_sub_board() {
+        assert_eq!(board_of(4, 7), 5);
+        assert_eq!(target_of(4, 7), 4);
+        assert_eq!(next_board(&board_with(&[], &[]), 0, 8), Some(2));
+        assert_eq!(describe(5), "rows 3-5, columns 6-8");
+    }
+
+    #[test]
+    fn a_decided_sub_board_frees_the_next_move() {
+        let board = board_with(&[(2, 1)], &[6]);
+        assert_eq!(sub_winner(&board, 2), "player1");
+        assert_eq!(sub_winner(&board, 6), "draw");
+        assert_eq!(next_board(&board, 3, 5), None);
+        assert_eq!(next_board(&board, 5, 3), None);
+        assert_eq!(next_board(&board, 5, 4), Some(7));
+    }
+
+    #[test]
+    fn three_sub_boards_in_a_row_win_and_drawn_ones_block() {
+        assert_eq!(winner(&board_with(&[(0, 2), (4, 2), (8, 2)], &[])), "player2");
+        assert_eq!(winner(&board_with(&[(0, 1), (1, 1)], &[2])), "");
+        assert_eq!(winner(&board_with(&[(0, 1), (4, 2)], &[1, 2, 3, 5, 6, 7, 8])), "draw");
+    }
+}
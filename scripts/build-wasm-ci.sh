@@ -1,22 +1,36 @@
 #!/bin/bash
 
-echo "🦀 Preparing WASM modules for CI/CD..."
-
-# Check if pre-built WASM files exist
-if [ -f "public/chess_wasm.wasm" ] && [ -f "public/tictactoe_wasm.wasm" ]; then
-    echo "✅ Pre-built WASM files found:"
-    echo "   - Chess WASM: $(ls -lh public/chess_wasm.wasm | awk '{print $5}')"
-    echo "   - Tic-Tac-Toe WASM: $(ls -lh public/tictactoe_wasm.wasm | awk '{print $5}')"
-    echo "   - Chess metadata: public/chess_metadata.json"
-    echo "   - Tic-Tac-Toe metadata: public/tictactoe_metadata.json"
-    echo "✅ WASM modules ready for deployment"
-    exit 0
-else
-    echo "❌ Pre-built WASM files not found"
-    echo "   Expected files:"
-    echo "   - public/chess_wasm.wasm"
-    echo "   - public/tictactoe_wasm.wasm"
-    echo "   - public/chess_metadata.json"
-    echo "   - public/tictactoe_metadata.json"
-    exit 1
+# Builds every game module from this tree into public/. Nothing is taken
+# from an earlier build, so a module that fails to build or traps on
+# scripts/test-wasm.js fails the job instead of shipping a stale copy.
+
+set -euo pipefail
+
+echo "🦀 Building WASM modules for CI/CD..."
+
+if ! rustup target list --installed | grep -q '^wasm32-unknown-unknown$'; then
+    rustup target add wasm32-unknown-unknown
 fi
+
+# crate directory, cargo package, module name
+MODULES=(
+    "rust-chess chess-wasm chess"
+    "rust-tictactoe tictactoe-wasm tictactoe"
+    "rust-kuhn-poker kuhn-poker-wasm kuhn_poker"
+)
+
+mkdir -p public
+for module in "${MODULES[@]}"; do
+    read -r crate package name <<< "$module"
+    echo "📦 Building $package..."
+    # The arena provides env.host_log for its transcript, and binary-state
+    # adds MessagePack states for bulk simulation
+    cargo build --release --target wasm32-unknown-unknown -p "$package" --features host-log,binary-state
+    cp "target/wasm32-unknown-unknown/release/${name}_wasm.wasm" public/
+    cp "$crate/metadata.json" "public/${name}_metadata.json"
+    echo "   - ${name}_wasm.wasm: $(ls -lh "public/${name}_wasm.wasm" | awk '{print $5}')"
+done
+
+node scripts/test-wasm.js public
+
+echo "✅ WASM modules ready for deployment"
@@ -2,19 +2,10 @@
 
 echo "🦀 Building WASM modules..."
 
-# Check if wasm-pack is available
+# Without wasm-pack, build the modules with cargo the way CI does
 if ! command -v wasm-pack &> /dev/null; then
-    echo "⚠️  wasm-pack not found. Checking if pre-built WASM files exist..."
-
-    if [ -f "public/chess_wasm.wasm" ] && [ -f "public/tictactoe_wasm.wasm" ]; then
-        echo "✅ Pre-built WASM files found in public directory"
-        echo "   Using existing WASM files for deployment"
-        exit 0
-    else
-        echo "❌ No pre-built WASM files found and wasm-pack is not available"
-        echo "   Install wasm-pack with: curl https://rustwasm.github.io/wasm-pack/installer/init.sh -sSf | sh"
-        exit 1
-    fi
+    echo "⚠️  wasm-pack not found, building with cargo instead"
+    exec ./scripts/build-wasm-ci.sh
 fi
 
 echo "📦 Building Chess WASM..."
@@ -24,10 +15,12 @@ if [ -f "build.sh" ]; then
     if ./build.sh; then
         echo "✅ Chess WASM built successfully"
     else
-        echo "⚠️  Chess WASM build failed, using pre-built version"
+        echo "❌ Chess WASM build failed"
+        exit 1
     fi
 else
-    echo "⚠️  No build.sh found in rust-chess, using pre-built version"
+    echo "❌ No build.sh found in rust-chess"
+    exit 1
 fi
 cd ..
 
@@ -38,10 +31,28 @@ if [ -f "build.sh" ]; then
     if ./build.sh; then
         echo "✅ Tic-Tac-Toe WASM built successfully"
     else
-        echo "⚠️  Tic-Tac-Toe WASM build failed, using pre-built version"
+        echo "❌ Tic-Tac-Toe WASM build failed"
+        exit 1
+    fi
+else
+    echo "❌ No build.sh found in rust-tictactoe"
+    exit 1
+fi
+cd ..
+
+echo "📦 Building Kuhn Poker WASM..."
+cd rust-kuhn-poker
+if [ -f "build.sh" ]; then
+    chmod +x build.sh
+    if ./build.sh; then
+        echo "✅ Kuhn Poker WASM built successfully"
+    else
+        echo "❌ Kuhn Poker WASM build failed"
+        exit 1
     fi
 else
-    echo "⚠️  No build.sh found in rust-tictactoe, using pre-built version"
+    echo "❌ No build.sh found in rust-kuhn-poker"
+    exit 1
 fi
 cd ..
 
@@ -0,0 +1,93 @@
+// Loads each game module the way the arena does and feeds it input the host
+// cannot vouch for. wasm32 builds abort on panic, so a trap here means an
+// export still has a panic path.
+//
+// Usage: node scripts/test-wasm.js [dir]   (defaults to public/)
+
+import { readFileSync } from 'node:fs';
+import { join } from 'node:path';
+
+const dir = process.argv[2] ?? 'public';
+const modules = ['chess_wasm.wasm', 'tictactoe_wasm.wasm', 'kuhn_poker_wasm.wasm'];
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+async function load(file) {
+  const bytes = readFileSync(join(dir, file));
+  const { instance } = await WebAssembly.instantiate(bytes, { env: { host_log: () => {} } });
+  return instance.exports;
+}
+
+// Writes raw bytes plus a terminating NUL, so a NUL inside `bytes` reaches
+// the module exactly as a careless host would send it.
+function write(exports, bytes) {
+  const ptr = exports.malloc(bytes.length + 1);
+  const memory = new Uint8Array(exports.memory.buffer);
+  memory.set(bytes, ptr);
+  memory[ptr + bytes.length] = 0;
+  return ptr;
+}
+
+function read(exports, ptr) {
+  const memory = new Uint8Array(exports.memory.buffer);
+  let end = ptr;
+  while (memory[end] !== 0) end++;
+  const text = decoder.decode(memory.slice(ptr, end));
+  exports.dealloc_string(ptr);
+  return text;
+}
+
+function call(exports, name, ...inputs) {
+  const ptrs = inputs.map(input => write(exports, typeof input === 'string' ? encoder.encode(input) : input));
+  try {
+    return read(exports, exports[name](...ptrs));
+  } finally {
+    ptrs.forEach(ptr => exports.free(ptr));
+  }
+}
+
+const isError = result => result.startsWith('ERROR:');
+
+// Each check returns the export's result and says what it should look like.
+const checks = {
+  // The NUL cuts the C string short, leaving a move that is not legal.
+  'NUL byte inside a move': (exports, state) => [call(exports, 'apply_move', state, encoder.encode('e2\0e4')), isError],
+  // Escaped in JSON the NUL survives parsing and is echoed in the error.
+  'NUL byte inside a move list': (exports, state) => [call(exports, 'apply_moves', state, '["e2\\u0000e4"]'), isError],
+  'state that is not JSON': exports => [call(exports, 'get_valid_moves', '{"board": \0}'), isError],
+  // A header is copied into the PGN as is, so the result string itself
+  // holds the NUL on its way out.
+  'NUL byte inside a PGN header': (exports, state) =>
+    exports.get_pgn
+      ? [call(exports, 'get_pgn', state, '{"Event": "Arena\\u0000Cup"}'), result => result.startsWith('[Event "ArenaCup"]')]
+      : null,
+};
+
+let failures = 0;
+for (const file of modules) {
+  const exports = await load(file);
+  const state = read(exports, exports.get_initial_state());
+  for (const [name, check] of Object.entries(checks)) {
+    let result;
+    let ok;
+    try {
+      const outcome = check(exports, state);
+      if (!outcome) continue;
+      const [answer, expected] = outcome;
+      result = answer;
+      ok = expected(answer);
+    } catch (error) {
+      result = `trapped: ${error.message}`;
+      ok = false;
+    }
+    failures += ok ? 0 : 1;
+    console.log(`${ok ? '✅' : '❌'} ${file}: ${name}${ok ? '' : ` -> ${result}`}`);
+  }
+}
+
+if (failures > 0) {
+  console.error(`❌ ${failures} check(s) failed`);
+  process.exit(1);
+}
+console.log('✅ No module trapped');
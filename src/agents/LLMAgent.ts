@@ -52,7 +52,8 @@ export class LLMAgent implements Agent {
 
       
       const validMoves = this.gameEngine.getValidMoves(state);
-      const sampleBoardDisplay = this.gameEngine.getBoardDisplay(state);
+      const sampleBoardDisplay =
+        this.gameEngine.getBoardDisplayFor?.(state, state.currentPlayer) ?? this.gameEngine.getBoardDisplay(state);
 
       this.promptTemplate = PromptTemplateManager.getPromptTemplate(
         metadata,
@@ -109,7 +110,8 @@ export class LLMAgent implements Agent {
     console.log(`🔍 LLMAgent: Game state board:`, typeof state.board === 'string' ? state.board.substring(0, 100) + '...' : state.board);
     console.log(`🔍 LLMAgent: Game state metadata:`, state.metadata);
 
-    const boardDisplay = this.gameEngine.getBoardDisplay(state);
+    // Players only see their own seat's view of hidden-information games.
+    const boardDisplay = this.gameEngine.getBoardDisplayFor?.(state, playerId) ?? this.gameEngine.getBoardDisplay(state);
     const validMoves = this.gameEngine.getValidMoves(state);
 
     console.log(`🔍 LLMAgent: Board display:`, boardDisplay?.substring(0, 200) + '...');
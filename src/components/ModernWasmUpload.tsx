@@ -1,7 +1,7 @@
 import React, { useState, useRef, useCallback } from 'react';
 import { Upload, FileText, Package, CheckCircle, AlertCircle, X, Sparkles, Zap, GamepadIcon } from 'lucide-react';
 import { wasmGameLoader } from '../services/WasmGameLoader';
-import { GameMetadata } from '../interfaces/WasmGameEngine';
+import { GameMetadata, readEmbeddedMetadata } from '../interfaces/WasmGameEngine';
 import { ModalPortal } from './ModalPortal';
 
 interface ModernWasmUploadProps {
@@ -108,8 +108,14 @@ export function ModernWasmUpload({ onClose, onGameUploaded }: ModernWasmUploadPr
 
 
       await WebAssembly.compile(wasmBytes);
-      
-      updateState({ wasmStatus: 'validated' });
+
+      // Modules built with embed_metadata! need no separate metadata.json.
+      const embedded = state.metadataFile ? null : await readEmbeddedMetadata(wasmBytes);
+      if (embedded?.name) {
+        updateState({ wasmStatus: 'validated', metadataStatus: 'validated', parsedMetadata: embedded });
+      } else {
+        updateState({ wasmStatus: 'validated' });
+      }
     } catch (error: unknown) {
       updateState({
         wasmStatus: 'error',
@@ -148,8 +154,8 @@ export function ModernWasmUpload({ onClose, onGameUploaded }: ModernWasmUploadPr
   };
 
   const handleUpload = async () => {
-    if (!state.wasmFile || !state.metadataFile || !state.parsedMetadata) {
-      updateState({ uploadError: 'Both WASM and metadata files are required' });
+    if (!state.wasmFile || !state.parsedMetadata) {
+      updateState({ uploadError: 'A metadata file is required for modules without embedded metadata' });
       return;
     }
 
@@ -375,6 +381,12 @@ export function ModernWasmUpload({ onClose, onGameUploaded }: ModernWasmUploadPr
                     <div className="text-xs text-gray-500">{(state.metadataFile.size / 1024).toFixed(1)} KB</div>
                   </div>
                 )}
+
+                {!state.metadataFile && state.parsedMetadata && (
+                  <div className="bg-gray-800/50 rounded-lg p-3 text-left">
+                    <div className="text-sm text-gray-300 font-medium">Embedded in the WASM module</div>
+                  </div>
+                )}
                 
                 {state.metadataError && (
                   <div className="bg-red-900/20 border border-red-500/30 rounded-lg p-3 text-left">
@@ -36,6 +36,13 @@ export class WasmGameEngineAdapter implements GameEngine {
     return this.loadedGame.metadata;
   }
 
+  // The GameState is handed to every agent, so games with hidden
+  // information leave their state out of it: it can reveal the opponent's
+  // cards. The wrapper keeps the state for the module either way.
+  private playerVisibleState(wasmState: string): string | undefined {
+    return this.wasmEngine.getCapabilities?.()?.hidden_info ? undefined : wasmState;
+  }
+
   private mapWasmPlayerToSystemPlayer(wasmPlayer: string): PlayerId {
     
     if (wasmPlayer === 'player1') return 'player1';
@@ -57,7 +64,7 @@ export class WasmGameEngineAdapter implements GameEngine {
     const currentPlayer = this.mapWasmPlayerToSystemPlayer(wasmCurrentPlayer);
 
     return {
-      board: wasmState,
+      board: this.playerVisibleState(wasmState),
       currentPlayer,
       moves: [],
       result: 'ongoing',
@@ -108,9 +115,10 @@ export class WasmGameEngineAdapter implements GameEngine {
     }
 
     
-    const isGameOver = this.wasmEngine.isGameOver();
-    const winner = isGameOver ? this.wasmEngine.getWinner() : null;
-    const currentPlayer = this.wasmEngine.getCurrentPlayer?.();
+    const turnInfo = this.wasmEngine.getTurnInfo?.();
+    const isGameOver = turnInfo ? turnInfo.game_over : this.wasmEngine.isGameOver();
+    const winner = isGameOver ? (turnInfo ? turnInfo.winner : this.wasmEngine.getWinner()) : null;
+    const currentPlayer = turnInfo ? turnInfo.current_player : this.wasmEngine.getCurrentPlayer?.();
 
     if (!currentPlayer) {
       throw new Error('WASM engine getCurrentPlayer() returned null/undefined after move');
@@ -135,7 +143,7 @@ export class WasmGameEngineAdapter implements GameEngine {
 
     
     return {
-      board: newWasmState,
+      board: this.playerVisibleState(newWasmState),
       currentPlayer: mappedCurrentPlayer, 
       moves: [...state.moves, move],
       result,
@@ -197,6 +205,14 @@ export class WasmGameEngineAdapter implements GameEngine {
     return this.wasmEngine.render();
   }
 
+  getBoardDisplayFor(_state: GameState, playerId: PlayerId): string {
+    return this.wasmEngine.renderFor?.(playerId) ?? this.wasmEngine.render();
+  }
+
+  drainLogs(): string[] {
+    return this.wasmEngine.drainLogs?.() ?? [];
+  }
+
   serializeState(state: GameState): string {
     return JSON.stringify({
       ...state,
@@ -12,6 +12,13 @@ export interface WasmGameEngine {
   getGameDescription?(): string;
   getMoveNotation?(move: string): string;
   logTranscript?(): string;
+  drainLogs?(): string[];
+  getTurnInfo?(): TurnInfo;
+  renderFor?(player: string): string;
+  getObservation?(player: string): unknown;
+  applyMoves?(moves: string[]): string;
+  getCapabilities?(): GameCapabilities | null;
+  getStateCapabilities?(): GameCapabilities | null;
 }
 
 
@@ -19,17 +26,65 @@ export interface WasmGameExports {
   memory: WebAssembly.Memory;
   malloc?: (size: number) => number;
   free?: (ptr: number) => void;
+  dealloc_string?: (ptr: number) => void;
   get_initial_state: () => number;
+  get_initial_state_with_config?: (configPtr: number) => number;
   get_valid_moves: (statePtr: number) => number;
   apply_move: (statePtr: number, movePtr: number) => number;
   is_game_over: (statePtr: number) => number;
   get_winner: (statePtr: number) => number;
   render: (statePtr: number) => number;
+  get_turn_info?: (statePtr: number) => number;
+  get_observation?: (statePtr: number, playerPtr: number) => number;
+  render_for?: (statePtr: number, playerPtr: number) => number;
+  apply_moves?: (statePtr: number, movesPtr: number) => number;
   get_game_name?: () => number;
   get_current_player?: (statePtr: number) => number;
   get_game_description?: () => number;
-  get_move_notation?: (movePtr: number) => number;
+  get_move_notation?: (statePtr: number, movePtr: number) => number;
   log_transcript?: (statePtr: number) => number;
+  get_abi_version?: () => number;
+  get_capabilities?: () => number;
+  get_state_capabilities?: (statePtr: number) => number;
+  get_last_error?: () => number;
+  set_log_level?: (level: number) => void;
+  get_metadata?: () => number;
+  get_state_formats?: () => number;
+  encode_state?: (statePtr: number) => number;
+  decode_state?: (ptr: number, len: number) => number;
+  handle_from_state?: (statePtr: number) => number;
+  handle_drop?: (handle: number) => void;
+  handle_apply_move?: (handle: number, movePtr: number) => number;
+  handle_get_state?: (handle: number) => number;
+  handle_get_valid_moves?: (handle: number) => number;
+  handle_is_game_over?: (handle: number) => number;
+  handle_get_winner?: (handle: number) => number;
+  handle_get_current_player?: (handle: number) => number;
+  handle_render?: (handle: number) => number;
+  handle_get_turn_info?: (handle: number) => number;
+  handle_get_observation?: (handle: number, playerPtr: number) => number;
+  handle_render_for?: (handle: number, playerPtr: number) => number;
+}
+
+
+export interface TurnInfo {
+  valid_moves: string[];
+  game_over: boolean;
+  winner: "player1" | "player2" | "draw" | null;
+  current_player: "player1" | "player2";
+  render: string;
+}
+
+
+export interface GameCapabilities {
+  players: number;
+  notation: boolean;
+  hints: boolean;
+  analysis: boolean;
+  draw_reasons: boolean;
+  custom_start: boolean;
+  hidden_info: boolean;
+  multiplayer: boolean;
 }
 
 
@@ -59,6 +114,9 @@ export interface GameMetadata {
 }
 
 
+const LOG_LEVELS = ['off', 'error', 'warn', 'info', 'debug'];
+const MAX_BUFFERED_LOGS = 1000;
+
 export class WasmGameWrapper implements WasmGameEngine {
   private wasmInstance: WebAssembly.Instance | null = null;
   protected exports: WasmGameExports | null = null;
@@ -66,6 +124,10 @@ export class WasmGameWrapper implements WasmGameEngine {
   private textEncoder = new TextEncoder();
   private textDecoder = new TextDecoder();
   private currentState: string = "";
+  // Set when the module has the handle exports: it then keeps the current
+  // state itself, and queries skip the JSON round trip.
+  private stateHandle: number | null = null;
+  private logs: string[] = [];
 
   constructor(metadata: GameMetadata) {
     this.metadata = metadata;
@@ -74,12 +136,14 @@ export class WasmGameWrapper implements WasmGameEngine {
   async initialize(wasmBytes: Uint8Array): Promise<boolean> {
     try {
       const importObject = {
-        env: {}
+        env: {
+          host_log: (level: number, ptr: number, len: number) => this.hostLog(level, ptr, len)
+        }
       };
 
       const wasmModule = await WebAssembly.compile(wasmBytes);
       this.wasmInstance = await WebAssembly.instantiate(wasmModule, importObject);
-      this.exports = this.wasmInstance.exports as unknown as WasmGameExports;
+      this.exports = this.guardExports(this.wasmInstance.exports as unknown as WasmGameExports);
 
       return this.validateExports();
     } catch (error) {
@@ -88,6 +152,65 @@ export class WasmGameWrapper implements WasmGameEngine {
     }
   }
 
+  // A panic inside the module traps on wasm32. Rethrow the trap as an Error
+  // carrying what the module recorded for get_last_error.
+  private guardExports(exports: WasmGameExports): WasmGameExports {
+    const guarded: Record<string, unknown> = {};
+    for (const [name, value] of Object.entries(exports)) {
+      if (typeof value !== 'function' || name === 'get_last_error') {
+        guarded[name] = value;
+        continue;
+      }
+      guarded[name] = (...args: unknown[]) => {
+        try {
+          return (value as (...args: unknown[]) => unknown)(...args);
+        } catch (error) {
+          if (error instanceof WebAssembly.RuntimeError) {
+            throw new Error(`WASM ${name} trapped: ${this.getLastError() ?? error.message}`);
+          }
+          throw error;
+        }
+      };
+    }
+    return guarded as unknown as WasmGameExports;
+  }
+
+  // Imported by modules built with the host-log feature: `len` bytes of
+  // UTF-8 at `ptr`, kept until the match controller drains them.
+  private hostLog(level: number, ptr: number, len: number): void {
+    if (!this.exports?.memory) return;
+
+    const bytes = new Uint8Array(this.exports.memory.buffer, ptr, len);
+    const label = LOG_LEVELS[level] ?? 'log';
+    if (this.logs.length >= MAX_BUFFERED_LOGS) {
+      this.logs.shift();
+    }
+    this.logs.push(`[${this.metadata.name} ${label}] ${this.textDecoder.decode(bytes)}`);
+  }
+
+  drainLogs(): string[] {
+    const logs = this.logs;
+    this.logs = [];
+    return logs;
+  }
+
+  // 0 silences the module, 1-4 runs from errors only to debug output.
+  setLogLevel(level: number): void {
+    this.exports?.set_log_level?.(level);
+  }
+
+  // The last panic or unreadable state reported by the module, if any.
+  getLastError(): string | null {
+    if (!this.exports?.get_last_error) {
+      return null;
+    }
+
+    const errorPtr = this.exports.get_last_error();
+    const error = this.readStringFromWasm(errorPtr);
+    this.freeWasmString(errorPtr);
+    return error || null;
+  }
+
   private validateExports(): boolean {
     if (!this.exports) return false;
 
@@ -148,33 +271,92 @@ export class WasmGameWrapper implements WasmGameEngine {
     return this.textDecoder.decode(bytes);
   }
 
-  protected freeWasmString(ptr: number): void {
-    if (this.exports?.free) {
+  // A state the module cannot read (malformed or corrupted JSON) comes
+  // back as an "ERROR: {...}" result instead of a silently reset board.
+  private checkStateResult(result: string, fn: string): string {
+    if (result.startsWith('ERROR:')) {
+      throw new Error(`WASM ${fn} failed: ${result}`);
+    }
+    return result;
+  }
+
+  // Inputs from writeStringToWasm go back through free; only blocks that
+  // came from malloc can be released.
+  protected freeWasmInput(ptr: number): void {
+    if (this.exports?.malloc && this.exports.free) {
       this.exports.free(ptr);
     }
-    
-    
+  }
+
+  // Strings returned by an export are released with dealloc_string.
+  protected freeWasmString(ptr: number): void {
+    if (this.exports?.dealloc_string) {
+      this.exports.dealloc_string(ptr);
+    }
+  }
+
+  // Calls the handle variant of a query when the module keeps the state,
+  // otherwise passes the state JSON in.
+  private queryState(
+    byHandle: ((handle: number) => number) | undefined,
+    byState: (statePtr: number) => number
+  ): string {
+    let resultPtr: number;
+    if (this.stateHandle !== null && byHandle) {
+      resultPtr = byHandle(this.stateHandle);
+    } else {
+      const statePtr = this.writeStringToWasm(this.currentState);
+      resultPtr = byState(statePtr);
+      this.freeWasmInput(statePtr);
+    }
+
+    const result = this.readStringFromWasm(resultPtr);
+    this.freeWasmString(resultPtr);
+    return result;
   }
 
   getInitialState(): string {
     if (!this.exports) throw new Error('WASM not initialized');
 
-    const ptr = this.exports.get_initial_state();
+    let ptr: number;
+    if (this.getCapabilities()?.hidden_info && this.exports.get_initial_state_with_config) {
+      // Modules have no randomness of their own; without a fresh seed every
+      // match of a game with hidden cards would be dealt the same ones.
+      const seed = crypto.getRandomValues(new Uint32Array(1))[0];
+      const configPtr = this.writeStringToWasm(JSON.stringify({ seed }));
+      ptr = this.exports.get_initial_state_with_config(configPtr);
+      this.freeWasmInput(configPtr);
+    } else {
+      ptr = this.exports.get_initial_state();
+    }
     const state = this.readStringFromWasm(ptr);
     this.freeWasmString(ptr);
-    this.currentState = state;
+    this.checkStateResult(state, 'get_initial_state');
+    this.keepState(state);
     return state;
   }
 
+  // Makes `state` current, replacing the copy the module keeps for the
+  // handle exports.
+  private keepState(state: string): void {
+    this.currentState = state;
+
+    if (this.exports?.handle_from_state) {
+      if (this.stateHandle !== null) {
+        this.exports.handle_drop?.(this.stateHandle);
+      }
+      const statePtr = this.writeStringToWasm(state);
+      const handle = this.exports.handle_from_state(statePtr);
+      this.freeWasmInput(statePtr);
+      this.stateHandle = handle > 0 ? handle : null;
+    }
+  }
+
   getValidMoves(): string[] {
     if (!this.exports) throw new Error('WASM not initialized');
 
-    const statePtr = this.writeStringToWasm(this.currentState);
-    const movesPtr = this.exports.get_valid_moves(statePtr);
-    const movesJson = this.readStringFromWasm(movesPtr);
-
-    this.freeWasmString(statePtr);
-    this.freeWasmString(movesPtr);
+    const movesJson = this.queryState(this.exports.handle_get_valid_moves, this.exports.get_valid_moves);
+    this.checkStateResult(movesJson, 'get_valid_moves');
 
     try {
       return JSON.parse(movesJson);
@@ -186,37 +368,60 @@ export class WasmGameWrapper implements WasmGameEngine {
   applyMove(move: string): string {
     if (!this.exports) throw new Error('WASM not initialized');
 
+    if (this.stateHandle !== null && this.exports.handle_apply_move && this.exports.handle_get_state) {
+      const movePtr = this.writeStringToWasm(move);
+      const status = this.exports.handle_apply_move(this.stateHandle, movePtr);
+      this.freeWasmInput(movePtr);
+
+      if (status < 0) {
+        return `ERROR: ${this.getLastError() ?? '{"code":"rejected","message":"the move was rejected"}'}`;
+      }
+
+      const statePtr = this.exports.handle_get_state(this.stateHandle);
+      this.currentState = this.readStringFromWasm(statePtr);
+      this.freeWasmString(statePtr);
+      return this.currentState;
+    }
+
     const statePtr = this.writeStringToWasm(this.currentState);
     const movePtr = this.writeStringToWasm(move);
     const newStatePtr = this.exports.apply_move(statePtr, movePtr);
     const newState = this.readStringFromWasm(newStatePtr);
 
-    this.freeWasmString(statePtr);
-    this.freeWasmString(movePtr);
+    this.freeWasmInput(statePtr);
+    this.freeWasmInput(movePtr);
     this.freeWasmString(newStatePtr);
 
-    this.currentState = newState;
+    // A rejected move comes back as "ERROR: {...}"; keep the last good state.
+    if (!newState.startsWith('ERROR:')) {
+      this.currentState = newState;
+    }
     return newState;
   }
 
   isGameOver(): boolean {
     if (!this.exports) throw new Error('WASM not initialized');
 
-    const statePtr = this.writeStringToWasm(this.currentState);
-    const result = this.exports.is_game_over(statePtr);
-    this.freeWasmString(statePtr);
+    let result: number;
+    if (this.stateHandle !== null && this.exports.handle_is_game_over) {
+      result = this.exports.handle_is_game_over(this.stateHandle);
+    } else {
+      const statePtr = this.writeStringToWasm(this.currentState);
+      result = this.exports.is_game_over(statePtr);
+      this.freeWasmInput(statePtr);
+    }
+
+    if (result < 0) {
+      throw new Error(`WASM is_game_over failed: ${this.getLastError() ?? 'the module could not read the current state'}`);
+    }
     return result === 1;
   }
 
   getWinner(): "player1" | "player2" | "draw" | null {
     if (!this.exports) throw new Error('WASM not initialized');
 
-    const statePtr = this.writeStringToWasm(this.currentState);
-    const winnerPtr = this.exports.get_winner(statePtr);
-    const winner = this.readStringFromWasm(winnerPtr);
-
-    this.freeWasmString(statePtr);
-    this.freeWasmString(winnerPtr);
+    const winner = this.queryState(this.exports.handle_get_winner, this.exports.get_winner);
+    this.checkStateResult(winner, 'get_winner');
 
     if (winner === 'player1' || winner === 'player2' || winner === 'draw') {
       return winner;
@@ -227,14 +432,146 @@ export class WasmGameWrapper implements WasmGameEngine {
   render(): string {
     if (!this.exports) throw new Error('WASM not initialized');
 
+    const rendered = this.queryState(this.exports.handle_render, this.exports.render);
+    return this.checkStateResult(rendered, 'render');
+  }
+
+  // Calls an export taking the state and a player name, or its handle
+  // variant when the module keeps the state.
+  private queryForPlayer(
+    player: string,
+    byHandle: ((handle: number, playerPtr: number) => number) | undefined,
+    byState: (statePtr: number, playerPtr: number) => number
+  ): string {
+    const playerPtr = this.writeStringToWasm(player);
+    const result = this.queryState(
+      byHandle && (handle => byHandle(handle, playerPtr)),
+      statePtr => byState(statePtr, playerPtr)
+    );
+    this.freeWasmInput(playerPtr);
+    return result;
+  }
+
+  // The board as `player` may see it. Games without hidden information
+  // show everyone the full render.
+  renderFor(player: string): string {
+    if (!this.exports) throw new Error('WASM not initialized');
+
+    if (!this.exports.render_for) {
+      return this.render();
+    }
+    const rendered = this.queryForPlayer(player, this.exports.handle_render_for, this.exports.render_for);
+    return this.checkStateResult(rendered, 'render_for');
+  }
+
+  // What `player` may know about the state, as parsed JSON.
+  getObservation(player: string): unknown {
+    if (!this.exports) throw new Error('WASM not initialized');
+
+    if (!this.exports.get_observation) {
+      return JSON.parse(this.currentState);
+    }
+    const observation = this.queryForPlayer(player, this.exports.handle_get_observation, this.exports.get_observation);
+    return JSON.parse(this.checkStateResult(observation, 'get_observation'));
+  }
+
+  // One call for everything the match loop asks about a position; modules
+  // without get_turn_info are queried export by export.
+  getTurnInfo(): TurnInfo {
+    if (!this.exports) throw new Error('WASM not initialized');
+
+    if (!this.exports.get_turn_info) {
+      return {
+        valid_moves: this.getValidMoves(),
+        game_over: this.isGameOver(),
+        winner: this.getWinner(),
+        current_player: this.getCurrentPlayer(),
+        render: this.render()
+      };
+    }
+
+    const infoJson = this.queryState(this.exports.handle_get_turn_info, this.exports.get_turn_info);
+    const info = JSON.parse(this.checkStateResult(infoJson, 'get_turn_info'));
+    return { ...info, winner: info.winner || null };
+  }
+
+  // Replays a whole sequence from the current state. Like applyMove, a
+  // rejected move returns "ERROR: {...}" and leaves the state unchanged.
+  applyMoves(moves: string[]): string {
+    if (!this.exports) throw new Error('WASM not initialized');
+
+    if (!this.exports.apply_moves) {
+      for (const move of moves) {
+        const result = this.applyMove(move);
+        if (result.startsWith('ERROR:')) return result;
+      }
+      return this.currentState;
+    }
+
     const statePtr = this.writeStringToWasm(this.currentState);
-    const renderPtr = this.exports.render(statePtr);
-    const rendered = this.readStringFromWasm(renderPtr);
+    const movesPtr = this.writeStringToWasm(JSON.stringify(moves));
+    const newStatePtr = this.exports.apply_moves(statePtr, movesPtr);
+    const newState = this.readStringFromWasm(newStatePtr);
 
-    this.freeWasmString(statePtr);
-    this.freeWasmString(renderPtr);
+    this.freeWasmInput(statePtr);
+    this.freeWasmInput(movesPtr);
+    this.freeWasmString(newStatePtr);
 
-    return rendered;
+    if (!newState.startsWith('ERROR:')) {
+      this.keepState(newState);
+    }
+    return newState;
+  }
+
+  // Formats the module converts states to and from; modules without
+  // get_state_formats only speak JSON.
+  getStateFormats(): string[] {
+    if (!this.exports?.get_state_formats) {
+      return ['json'];
+    }
+
+    const formatsPtr = this.exports.get_state_formats();
+    const formats = this.readStringFromWasm(formatsPtr);
+    this.freeWasmString(formatsPtr);
+    return JSON.parse(formats);
+  }
+
+  // The current (or given) JSON state as MessagePack.
+  encodeState(state: string = this.currentState): Uint8Array {
+    if (!this.exports?.encode_state || !this.getStateFormats().includes('msgpack')) {
+      throw new Error('WASM module does not support binary states');
+    }
+
+    const statePtr = this.writeStringToWasm(state);
+    const bufferPtr = this.exports.encode_state(statePtr);
+    this.freeWasmInput(statePtr);
+
+    if (bufferPtr === 0) {
+      throw new Error(`WASM encode_state failed: ${this.getLastError() ?? 'the state could not be encoded'}`);
+    }
+
+    // The buffer is a little-endian u32 length followed by the bytes.
+    const view = new DataView(this.exports.memory.buffer);
+    const length = view.getUint32(bufferPtr, true);
+    const bytes = new Uint8Array(this.exports.memory.buffer, bufferPtr + 4, length).slice();
+    this.exports.free?.(bufferPtr);
+    return bytes;
+  }
+
+  // A MessagePack state back to JSON, without making it current.
+  decodeState(bytes: Uint8Array): string {
+    if (!this.exports?.decode_state || !this.exports.malloc) {
+      throw new Error('WASM module does not support binary states');
+    }
+
+    const ptr = this.exports.malloc(bytes.length);
+    new Uint8Array(this.exports.memory.buffer).set(bytes, ptr);
+    const statePtr = this.exports.decode_state(ptr, bytes.length);
+    this.freeWasmInput(ptr);
+
+    const state = this.readStringFromWasm(statePtr);
+    this.freeWasmString(statePtr);
+    return this.checkStateResult(state, 'decode_state');
   }
 
   getGameName(): string {
@@ -249,12 +586,8 @@ export class WasmGameWrapper implements WasmGameEngine {
 
   getCurrentPlayer(): "player1" | "player2" {
     if (this.exports?.get_current_player) {
-      const statePtr = this.writeStringToWasm(this.currentState);
-      const playerPtr = this.exports.get_current_player(statePtr);
-      const player = this.readStringFromWasm(playerPtr);
-
-      this.freeWasmString(statePtr);
-      this.freeWasmString(playerPtr);
+      const player = this.queryState(this.exports.handle_get_current_player, this.exports.get_current_player);
+      this.checkStateResult(player, 'get_current_player');
 
       
       if (player === 'player1') return 'player1';
@@ -281,6 +614,55 @@ export class WasmGameWrapper implements WasmGameEngine {
     return { ...this.metadata };
   }
 
+  // Modules built before the discovery exports existed report version 0.
+  getAbiVersion(): number {
+    return this.exports?.get_abi_version ? this.exports.get_abi_version() : 0;
+  }
+
+  getCapabilities(): GameCapabilities | null {
+    if (!this.exports?.get_capabilities) {
+      return null;
+    }
+
+    const capabilitiesPtr = this.exports.get_capabilities();
+    const capabilities = this.readStringFromWasm(capabilitiesPtr);
+    this.freeWasmString(capabilitiesPtr);
+
+    try {
+      return JSON.parse(capabilities);
+    } catch {
+      return null;
+    }
+  }
+
+  // What holds for the current state, e.g. no tic-tac-toe hints on a board
+  // too large to solve. Modules without the export answer for every state.
+  getStateCapabilities(): GameCapabilities | null {
+    if (!this.exports?.get_state_capabilities) {
+      return this.getCapabilities();
+    }
+
+    const capabilities = this.queryState(undefined, this.exports.get_state_capabilities);
+    return JSON.parse(this.checkStateResult(capabilities, 'get_state_capabilities'));
+  }
+
+  getMoveNotation(move: string): string {
+    if (!this.exports?.get_move_notation) {
+      return move;
+    }
+
+    const statePtr = this.writeStringToWasm(this.currentState);
+    const movePtr = this.writeStringToWasm(move);
+    const notationPtr = this.exports.get_move_notation(statePtr, movePtr);
+    const notation = this.readStringFromWasm(notationPtr);
+
+    this.freeWasmInput(statePtr);
+    this.freeWasmInput(movePtr);
+    this.freeWasmString(notationPtr);
+
+    return notation || move;
+  }
+
   logTranscript?(): string {
     if (!this.exports?.log_transcript) {
       return `Transcript logging not supported by ${this.metadata.name}`;
@@ -290,10 +672,10 @@ export class WasmGameWrapper implements WasmGameEngine {
     const transcriptPtr = this.exports.log_transcript(statePtr);
     const transcript = this.readStringFromWasm(transcriptPtr);
 
-    this.freeWasmString(statePtr);
+    this.freeWasmInput(statePtr);
     this.freeWasmString(transcriptPtr);
 
-    return transcript;
+    return this.checkStateResult(transcript, 'log_transcript');
   }
 }
 
@@ -311,6 +693,7 @@ export async function validateWasmGame(wasmBytes: Uint8Array): Promise<boolean>
     const importObject = {
       env: {
         abort: () => { throw new Error('WASM abort called'); },
+        host_log: () => {},
         __wbindgen_throw: () => {
           throw new Error('WASM throw called');
         }
@@ -375,6 +758,38 @@ export async function validateWasmGame(wasmBytes: Uint8Array): Promise<boolean>
 }
 
 
+// Metadata compiled into the module by the SDK's embed_metadata!, read from
+// the llm_arena_metadata custom section or, if a build step stripped it,
+// from the get_metadata export. Null when the module carries none.
+export async function readEmbeddedMetadata(wasmBytes: Uint8Array): Promise<GameMetadata | null> {
+  try {
+    const module = await WebAssembly.compile(wasmBytes);
+    const textDecoder = new TextDecoder();
+
+    const sections = WebAssembly.Module.customSections(module, 'llm_arena_metadata');
+    if (sections.length > 0) {
+      return JSON.parse(textDecoder.decode(sections[0]));
+    }
+
+    const instance = await WebAssembly.instantiate(module, { env: { host_log: () => {} } });
+    const exports = instance.exports as unknown as WasmGameExports;
+    if (!exports.get_metadata) {
+      return null;
+    }
+
+    const ptr = exports.get_metadata();
+    const memory = new Uint8Array(exports.memory.buffer);
+    const end = memory.indexOf(0, ptr);
+    const metadata = textDecoder.decode(memory.slice(ptr, end < 0 ? memory.length : end));
+    exports.dealloc_string?.(ptr);
+    return JSON.parse(metadata);
+  } catch (error) {
+    console.warn('Could not read embedded metadata:', error);
+    return null;
+  }
+}
+
+
 export async function createWasmGame(
   wasmBytes: Uint8Array,
   metadata: GameMetadata
@@ -5,7 +5,8 @@ import {
   WasmGameEngine,
   GameMetadata,
   validateWasmGame,
-  createWasmGame
+  createWasmGame,
+  readEmbeddedMetadata
 } from '../interfaces/WasmGameEngine';
 
 
@@ -71,8 +72,7 @@ export class WasmGameLoader {
         const metadataText = await gamePackage.metadataFile.text();
         metadata = JSON.parse(metadataText);
       } else {
-        
-        metadata = this.createDefaultMetadata(gamePackage.wasmFile.name);
+        metadata = await this.embeddedOrDefaultMetadata(wasmBytes, gamePackage.wasmFile.name);
       }
 
       return await this.loadGameFromBytes(wasmBytes, metadata);
@@ -177,10 +177,10 @@ export class WasmGameLoader {
         if (metadataResponse.ok) {
           metadata = await metadataResponse.json();
         } else {
-          metadata = this.createDefaultMetadata(wasmUrl);
+          metadata = await this.embeddedOrDefaultMetadata(wasmBytes, wasmUrl);
         }
       } else {
-        metadata = this.createDefaultMetadata(wasmUrl);
+        metadata = await this.embeddedOrDefaultMetadata(wasmBytes, wasmUrl);
       }
 
       return await this.loadGameFromBytes(wasmBytes, metadata);
@@ -305,7 +305,12 @@ export class WasmGameLoader {
     return errors;
   }
 
-  
+  // A module built with the SDK's embed_metadata! describes itself; only
+  // modules without it get a name guessed from the file name.
+  private async embeddedOrDefaultMetadata(wasmBytes: Uint8Array, filename: string): Promise<GameMetadata> {
+    return (await readEmbeddedMetadata(wasmBytes)) ?? this.createDefaultMetadata(filename);
+  }
+
   private createDefaultMetadata(filename: string): GameMetadata {
     const name = filename.replace(/\.(wasm|js)$/, '').replace(/[-_]/g, ' ');
     
@@ -38,6 +38,10 @@ export interface GameEngine {
   deserializeState(serialized: string): GameState;
   validateMove(state: GameState, move: Move): boolean;
   getBoardDisplay(state: GameState): string;
+  // The board as playerId may see it, for games with hidden information.
+  getBoardDisplayFor?(state: GameState, playerId: PlayerId): string;
+  // Diagnostics emitted since the last call, for the transcript panel.
+  drainLogs?(): string[];
 }
 
 
@@ -129,6 +129,7 @@ export class MatchController {
           this.currentState = await this.applyMoveAsync(this.currentState, move);
 
           this.addToTranscript(`Move: ${JSON.stringify(move.position || move.data)}`);
+          this.addEngineLogs();
           const boardDisplay = await this.getBoardDisplayAsync(this.currentState);
           this.addToTranscript(`Board:\n${boardDisplay}`);
 
@@ -142,11 +143,13 @@ export class MatchController {
           }
         } else {
           this.addToTranscript(`Invalid move attempted: ${JSON.stringify(move.position || move.data)}`);
+          this.addEngineLogs();
           this.events.onMoveAttempt?.(move, false);
           continue;
         }
       } catch (error) {
         this.addToTranscript(`Error during ${agent.name}'s turn: ${error}`);
+        this.addEngineLogs();
         this.events.onError?.(error as Error);
         return;
       }
@@ -193,6 +196,12 @@ export class MatchController {
     this.transcript.push(`[${new Date().toISOString()}] ${message}`);
   }
 
+  private addEngineLogs(): void {
+    for (const entry of this.gameEngine.drainLogs?.() ?? []) {
+      this.addToTranscript(entry);
+    }
+  }
+
   async makeMove(move: Move): Promise<boolean> {
     if (await this.validateMoveAsync(this.currentState, move)) {
       this.currentState = await this.applyMoveAsync(this.currentState, move);
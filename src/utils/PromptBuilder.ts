@@ -17,7 +17,7 @@ export class PromptBuilder {
 
   buildPrompt(state: GameState, playerId: PlayerId): string {
     const validMoves = this.gameEngine.getValidMoves(state);
-    const boardDisplay = this.gameEngine.getBoardDisplay(state);
+    const boardDisplay = this.gameEngine.getBoardDisplayFor?.(state, playerId) ?? this.gameEngine.getBoardDisplay(state);
     const moveHistory = this.formatMoveHistory(state.moves);
 
     return `${this.template.system}